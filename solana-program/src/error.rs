@@ -57,6 +57,22 @@ pub enum SolanaOpenApiError {
     /// Invalid message options
     #[error("Invalid message options")]
     InvalidMessageOptions,
+
+    /// Guardian set has expired
+    #[error("Guardian set has expired")]
+    GuardianSetExpired,
+
+    /// Guardian signatures did not reach quorum
+    #[error("Guardian signatures did not reach quorum")]
+    GuardianQuorumNotMet,
+
+    /// Inbound nonce has already been executed for this channel
+    #[error("Inbound nonce has already been executed for this channel")]
+    NonceTooLow,
+
+    /// Inbound nonce is too far ahead of the tracked window for this channel
+    #[error("Inbound nonce is too far ahead of the tracked window for this channel")]
+    NonceOutOfRange,
 }
 
 impl From<SolanaOpenApiError> for ProgramError {
@@ -82,6 +98,12 @@ impl FromPrimitive for SolanaOpenApiError {
             8 => Some(SolanaOpenApiError::InvalidMessageStatus),
             9 => Some(SolanaOpenApiError::InvalidDestinationChain),
             10 => Some(SolanaOpenApiError::MessageNotFound),
+            11 => Some(SolanaOpenApiError::PayloadTooLarge),
+            12 => Some(SolanaOpenApiError::InvalidMessageOptions),
+            13 => Some(SolanaOpenApiError::GuardianSetExpired),
+            14 => Some(SolanaOpenApiError::GuardianQuorumNotMet),
+            15 => Some(SolanaOpenApiError::NonceTooLow),
+            16 => Some(SolanaOpenApiError::NonceOutOfRange),
             _ => None,
         }
     }
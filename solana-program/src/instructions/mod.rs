@@ -7,3 +7,5 @@ pub mod send_message;
 pub mod receive_message;
 pub mod query_data;
 pub mod process_response;
+pub mod process_vaa;
+pub mod send_nft_bridge;
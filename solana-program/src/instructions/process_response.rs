@@ -6,19 +6,29 @@
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
+    program::invoke_signed,
     pubkey::Pubkey,
     program_error::ProgramError,
+    rent::Rent,
+    system_instruction,
     sysvar::{clock::Clock, Sysvar},
     msg,
 };
 use borsh::{BorshDeserialize, BorshSerialize};
 
 use crate::error::SolanaOpenApiError;
-use crate::layerzero::{CrossChainMessage, verify_from_endpoint};
-use crate::state::{MessageRecord, ProgramConfig, MessageStatus};
+use crate::layerzero::{CrossChainMessage, GuardianSignature, verify_from_endpoint};
+use crate::state::{InboundNonceTracker, MessageRecord, ProgramConfig, MessageStatus};
 
 // Unused imports have been removed
 
+/// Size of the `InboundNonceTracker`'s out-of-order execution window.
+const NONCE_BITMAP_WIDTH: u64 = 64;
+
+/// Seed for the inbound-nonce-tracker PDA, derived per-channel from
+/// `(src_eid, sender)`.
+const NONCE_TRACKER_SEED: &[u8] = b"nonce";
+
 /// Response data instruction parameters
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct ResponseData {
@@ -26,12 +36,25 @@ pub struct ResponseData {
     pub source_address: Vec<u8>,
     pub response_payload: Vec<u8>,
     pub original_message_id: [u8; 32],
+    /// Index of the guardian set the relayer signed this response against.
+    pub guardian_set_index: u32,
+    /// Guardian attestations over the `verify_from_endpoint` preimage (this
+    /// response's routing fields, `response_payload`, and its channel
+    /// binding), in strictly increasing guardian index order, sufficient to
+    /// reach quorum.
+    pub guardian_signatures: Vec<GuardianSignature>,
+    /// LayerZero source endpoint ID for this channel.
+    pub src_eid: u32,
+    /// LayerZero sender address (the remote OApp) for this channel.
+    pub sender: [u8; 32],
+    /// This channel's inbound nonce for the message being delivered.
+    pub nonce: u64,
 }
 
 /// Process a cross-chain response instruction
-pub fn process(
+pub fn process<'a>(
     program_id: &Pubkey,
-    accounts: &[AccountInfo],
+    accounts: &'a [AccountInfo<'a>],
     instruction_data: &[u8],
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
@@ -39,8 +62,11 @@ pub fn process(
     // Get required accounts
     let relayer_account = next_account_info(accounts_iter)?;
     let message_account = next_account_info(accounts_iter)?;
+    let nonce_tracker_account = next_account_info(accounts_iter)?;
+    let claim_account = next_account_info(accounts_iter)?;
     let config_account = next_account_info(accounts_iter)?;
     let layerzero_endpoint = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
     let clock_sysvar = next_account_info(accounts_iter)?;
 
     // Verify relayer is signer
@@ -67,26 +93,120 @@ pub fn process(
     }
 
     // Create a cross-chain message for verification
-    let nonce = 0; // Nonce is not relevant for received responses
     let message = CrossChainMessage::new(
         response_data.source_chain_id,
         config.solana_chain_id, // destination is this chain
         0, // Message type is not relevant for verification
         response_data.response_payload.clone(),
-        nonce,
+        response_data.nonce,
         None, // No options needed for verification
     );
 
-    // Verify message from LayerZero endpoint
+    // Get current timestamp
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    let timestamp = clock.unix_timestamp as u64;
+
+    // Bind the guardian signature to this response's channel (`src_eid`,
+    // `sender`) and the specific pending query it's meant to complete
+    // (`original_message_id`), so a signature guardians made over one
+    // response can't be spliced onto a different channel's nonce tracker or
+    // a different, unrelated pending `message_account`.
+    let mut channel_binding = Vec::new();
+    channel_binding.extend_from_slice(&response_data.src_eid.to_le_bytes());
+    channel_binding.extend_from_slice(&response_data.sender);
+    channel_binding.extend_from_slice(&response_data.original_message_id);
+
+    // Verify message from LayerZero endpoint against the active guardian set
+    // and atomically claim it so the same message can't be delivered twice.
     verify_from_endpoint(
         program_id,
         layerzero_endpoint,
         &message,
+        &channel_binding,
+        &config.guardian_set,
+        response_data.guardian_set_index,
+        timestamp,
+        &response_data.guardian_signatures,
+        relayer_account,
+        claim_account,
+        system_program,
     )?;
 
-    // Get current timestamp
-    let clock = Clock::from_account_info(clock_sysvar)?;
-    let timestamp = clock.unix_timestamp as u64;
+    // Enforce per-channel inbound nonce ordering (LayerZero's lazy-inbound-nonce
+    // model): nonces at or below the high-water mark are rejected as replays,
+    // and out-of-order nonces above it are tracked in a bitmap until the gaps
+    // fill in and the mark can advance contiguously. The tracker account is a
+    // PDA derived from `(src_eid, sender)`, so a relayer can't reset a
+    // channel's nonce state by pointing at a different fresh account.
+    let (nonce_tracker_pda, nonce_tracker_bump) = Pubkey::find_program_address(
+        &[NONCE_TRACKER_SEED, &response_data.src_eid.to_le_bytes(), &response_data.sender],
+        program_id,
+    );
+    if nonce_tracker_pda != *nonce_tracker_account.key {
+        return Err(SolanaOpenApiError::InvalidAccountData.into());
+    }
+
+    let mut nonce_tracker = if nonce_tracker_account.data_is_empty() {
+        let tracker = InboundNonceTracker {
+            is_initialized: true,
+            src_eid: response_data.src_eid,
+            sender: response_data.sender,
+            inbound_nonce: 0,
+            executed_bitmap: 0,
+        };
+        let mut tracker_data = Vec::new();
+        tracker
+            .serialize(&mut tracker_data)
+            .map_err(|_| ProgramError::AccountDataTooSmall)?;
+
+        let rent = Rent::get()?;
+        let lamports = rent.minimum_balance(tracker_data.len());
+        invoke_signed(
+            &system_instruction::create_account(
+                relayer_account.key,
+                nonce_tracker_account.key,
+                lamports,
+                tracker_data.len() as u64,
+                program_id,
+            ),
+            &[relayer_account.clone(), nonce_tracker_account.clone(), system_program.clone()],
+            &[&[
+                NONCE_TRACKER_SEED,
+                &response_data.src_eid.to_le_bytes(),
+                &response_data.sender,
+                &[nonce_tracker_bump],
+            ]],
+        )?;
+        tracker
+    } else {
+        InboundNonceTracker::try_from_slice(&nonce_tracker_account.data.borrow())
+            .map_err(|_| SolanaOpenApiError::InvalidAccountData)?
+    };
+
+    if nonce_tracker.src_eid != response_data.src_eid || nonce_tracker.sender != response_data.sender {
+        return Err(SolanaOpenApiError::InvalidAccountData.into());
+    }
+
+    if response_data.nonce <= nonce_tracker.inbound_nonce {
+        return Err(SolanaOpenApiError::NonceTooLow.into());
+    }
+    let offset = response_data.nonce - nonce_tracker.inbound_nonce - 1;
+    if offset >= NONCE_BITMAP_WIDTH {
+        return Err(SolanaOpenApiError::NonceOutOfRange.into());
+    }
+    let bit = 1u64 << offset;
+    if nonce_tracker.executed_bitmap & bit != 0 {
+        return Err(SolanaOpenApiError::MessageAlreadyProcessed.into());
+    }
+    nonce_tracker.executed_bitmap |= bit;
+    while nonce_tracker.executed_bitmap & 1 != 0 {
+        nonce_tracker.executed_bitmap >>= 1;
+        nonce_tracker.inbound_nonce += 1;
+    }
+
+    nonce_tracker
+        .serialize(&mut *nonce_tracker_account.data.borrow_mut())
+        .map_err(|_| ProgramError::AccountDataTooSmall)?;
 
     // Get message record
     let mut message_record = MessageRecord::try_from_slice(&message_account.data.borrow())
@@ -118,6 +238,7 @@ pub fn process(
     msg!("Source Address: {:?}", response_data.source_address);
     msg!("Response Size: {} bytes", response_data.response_payload.len());
     msg!("Response received at: {} (unix timestamp)", timestamp);
+    msg!("Channel: src_eid={} nonce={} inbound_nonce={}", response_data.src_eid, response_data.nonce, nonce_tracker.inbound_nonce);
 
     Ok(())
 }
@@ -0,0 +1,172 @@
+/**
+ * @file instructions/process_vaa.rs
+ * @description Instruction handler for settling Wormhole VAAs alongside LayerZero V2 messages
+ */
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    program_error::ProgramError,
+    rent::Rent,
+    system_instruction,
+    sysvar::{clock::Clock, Sysvar},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::SolanaOpenApiError;
+use crate::layerzero::{verify_vaa, CrossChainMessage, Vaa};
+use crate::state::{MessageRecord, MessageStatus, ProgramConfig, ReplayProtection};
+
+/// Seed for the replay-protection PDA, derived per-VAA from
+/// `(emitter_chain, emitter_address, sequence)`.
+const REPLAY_SEED: &[u8] = b"replay";
+
+/// Process-VAA instruction data
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ProcessVaaData {
+    pub vaa: Vaa,
+}
+
+/// Process a Wormhole VAA, settling it into the same `MessageRecord` state
+/// machine LayerZero messages use via `process_response`.
+pub fn process<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    // Get required accounts
+    let relayer_account = next_account_info(accounts_iter)?;
+    let message_account = next_account_info(accounts_iter)?;
+    let replay_account = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let clock_sysvar = next_account_info(accounts_iter)?;
+
+    // Verify relayer is signer
+    if !relayer_account.is_signer {
+        return Err(SolanaOpenApiError::Unauthorized.into());
+    }
+
+    // Deserialize the instruction data
+    let vaa_data = ProcessVaaData::try_from_slice(instruction_data)
+        .map_err(|_| SolanaOpenApiError::InvalidInstructionData)?;
+
+    // Get program config
+    let config = ProgramConfig::try_from_slice(&config_account.data.borrow())
+        .map_err(|_| SolanaOpenApiError::InvalidAccountData)?;
+
+    // Verify config is initialized
+    if !config.is_initialized {
+        return Err(SolanaOpenApiError::AccountNotInitialized.into());
+    }
+
+    // Get current timestamp
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    let timestamp = clock.unix_timestamp as u64;
+
+    // Verify guardian-set identity/expiry, the registered-emitter allowlist,
+    // and guardian quorum over the VAA body in one call.
+    let body = verify_vaa(&config.guardian_set, &config.registered_emitters, timestamp, &vaa_data.vaa)?;
+
+    // Enforce replay protection: the replay account is a PDA derived from
+    // `(emitter_chain, emitter_address, sequence)`, so re-submitting the same
+    // VAA resolves to the same account and finds it already initialized.
+    let (replay_pda, replay_bump) = Pubkey::find_program_address(
+        &[REPLAY_SEED, &body.emitter_chain.to_le_bytes(), &body.emitter_address, &body.sequence.to_le_bytes()],
+        program_id,
+    );
+    if replay_pda != *replay_account.key {
+        return Err(SolanaOpenApiError::InvalidAccountData.into());
+    }
+
+    if replay_account.data_is_empty() {
+        let replay = ReplayProtection {
+            is_initialized: true,
+            emitter_chain: body.emitter_chain,
+            emitter_address: body.emitter_address,
+            sequence: body.sequence,
+        };
+        let mut replay_data = Vec::new();
+        replay
+            .serialize(&mut replay_data)
+            .map_err(|_| ProgramError::AccountDataTooSmall)?;
+
+        let rent = Rent::get()?;
+        let lamports = rent.minimum_balance(replay_data.len());
+        invoke_signed(
+            &system_instruction::create_account(
+                relayer_account.key,
+                replay_account.key,
+                lamports,
+                replay_data.len() as u64,
+                program_id,
+            ),
+            &[relayer_account.clone(), replay_account.clone(), system_program.clone()],
+            &[&[
+                REPLAY_SEED,
+                &body.emitter_chain.to_le_bytes(),
+                &body.emitter_address,
+                &body.sequence.to_le_bytes(),
+                &[replay_bump],
+            ]],
+        )?;
+        replay
+            .serialize(&mut *replay_account.data.borrow_mut())
+            .map_err(|_| ProgramError::AccountDataTooSmall)?;
+    } else {
+        return Err(SolanaOpenApiError::MessageAlreadyProcessed.into());
+    }
+
+    // Derive a message ID the same way LayerZero messages do, so both
+    // providers' settlements are indistinguishable to downstream consumers.
+    let message_id = CrossChainMessage::new(
+        body.emitter_chain as u32,
+        config.solana_chain_id,
+        0, // same message type the record below is created with
+        body.payload.clone(),
+        body.sequence,
+        None,
+    )
+    .generate_id();
+
+    // Check if message account is already initialized
+    if message_account.data_is_empty() {
+        let mut message_record = MessageRecord::new(
+            message_id,
+            body.emitter_chain as u32,
+            config.solana_chain_id, // destination is this chain
+            0, // message type is carried in the payload, not this field, for VAAs
+            *relayer_account.key,
+            timestamp,
+        );
+        message_record.update_status(MessageStatus::Delivered);
+        message_record
+            .serialize(&mut *message_account.data.borrow_mut())
+            .map_err(|_| ProgramError::AccountDataTooSmall)?;
+    } else {
+        let mut message_record = MessageRecord::try_from_slice(&message_account.data.borrow())
+            .map_err(|_| SolanaOpenApiError::InvalidAccountData)?;
+
+        if message_record.status == MessageStatus::Completed as u8 {
+            return Err(SolanaOpenApiError::MessageAlreadyProcessed.into());
+        }
+
+        message_record.update_status(MessageStatus::Delivered);
+        message_record
+            .serialize(&mut *message_account.data.borrow_mut())
+            .map_err(|_| ProgramError::AccountDataTooSmall)?;
+    }
+
+    msg!("Wormhole VAA settled successfully");
+    msg!("Message ID: {:?}", message_id);
+    msg!("Emitter Chain: {}", body.emitter_chain);
+    msg!("Sequence: {}", body.sequence);
+    msg!("Payload Size: {} bytes", body.payload.len());
+
+    Ok(())
+}
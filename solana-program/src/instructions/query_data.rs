@@ -27,9 +27,9 @@ pub struct QueryDataParams {
 }
 
 /// Process a cross-chain data query instruction
-pub fn process(
+pub fn process<'a>(
     program_id: &Pubkey,
-    accounts: &[AccountInfo],
+    accounts: &'a [AccountInfo<'a>],
     instruction_data: &[u8],
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
@@ -42,6 +42,11 @@ pub fn process(
     let fee_account = next_account_info(accounts_iter)?;
     let clock_sysvar = next_account_info(accounts_iter)?;
 
+    // Any remaining accounts are address lookup tables the caller wants the
+    // endpoint to resolve the destination address/options payload against,
+    // so large payloads don't exceed the legacy account limit.
+    let lookup_table_accounts: Vec<AccountInfo<'a>> = accounts_iter.cloned().collect();
+
     // Verify sender is signer
     if !sender_account.is_signer {
         return Err(SolanaOpenApiError::Unauthorized.into());
@@ -80,6 +85,8 @@ pub fn process(
         refund_address: *sender_account.key,
         executor_options: Vec::new(), // Default options
         receiver_options: Vec::new(), // Default options
+        use_onchain_quote: true,
+        lookup_table_accounts: lookup_table_accounts.iter().map(|a| *a.key).collect(),
     };
 
     // Serialize the query parameters as the payload
@@ -131,6 +138,7 @@ pub fn process(
         layerzero_endpoint,
         fee_account,
         sender_account,
+        &lookup_table_accounts,
         &message,
         query_data.destination_address,
     )?;
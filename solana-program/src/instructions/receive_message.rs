@@ -14,7 +14,7 @@ use solana_program::{
 use borsh::{BorshDeserialize, BorshSerialize};
 
 use crate::error::SolanaOpenApiError;
-use crate::layerzero::{CrossChainMessage, verify_from_endpoint};
+use crate::layerzero::{CrossChainMessage, GuardianSignature, verify_from_endpoint};
 use crate::state::{MessageRecord, ProgramConfig, MessageStatus};
 
 // Unused imports have been removed
@@ -26,6 +26,12 @@ pub struct ReceiveMessageData {
     pub source_address: Vec<u8>,
     pub payload: Vec<u8>,
     pub message_type: u8,
+    /// Index of the guardian set the relayer signed this message against.
+    pub guardian_set_index: u32,
+    /// Guardian attestations over the `verify_from_endpoint` preimage (this
+    /// message's routing fields and `payload`), in strictly increasing
+    /// guardian index order, sufficient to reach quorum.
+    pub guardian_signatures: Vec<GuardianSignature>,
 }
 
 /// Process a receive message instruction
@@ -39,8 +45,10 @@ pub fn process<'a>(
     // Get required accounts
     let relayer_account = next_account_info(accounts_iter)?;
     let message_account = next_account_info(accounts_iter)?;
+    let claim_account = next_account_info(accounts_iter)?;
     let config_account = next_account_info(accounts_iter)?;
     let layerzero_endpoint = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
     let clock_sysvar = next_account_info(accounts_iter)?;
 
     // Verify relayer is signer
@@ -77,20 +85,29 @@ pub fn process<'a>(
         None, // No options needed for received messages
     );
 
-    // Verify message from LayerZero endpoint
+    // Get current timestamp
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    let timestamp = clock.unix_timestamp as u64;
+
+    // Verify message from LayerZero endpoint against the active guardian set
+    // and atomically claim it so the same message can't be delivered twice.
     verify_from_endpoint(
         program_id,
         layerzero_endpoint,
         &message,
+        &[],
+        &config.guardian_set,
+        receive_data.guardian_set_index,
+        timestamp,
+        &receive_data.guardian_signatures,
+        relayer_account,
+        claim_account,
+        system_program,
     )?;
 
     // Generate message ID
     let message_id = message.generate_id();
 
-    // Get current timestamp
-    let clock = Clock::from_account_info(clock_sysvar)?;
-    let timestamp = clock.unix_timestamp as u64;
-
     // Check if message account is already initialized
     if message_account.data_is_empty() {
         // Create new message record
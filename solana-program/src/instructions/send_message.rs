@@ -43,6 +43,11 @@ pub fn process<'a>(
     let fee_account = next_account_info(accounts_iter)?;
     let clock_sysvar = next_account_info(accounts_iter)?;
 
+    // Any remaining accounts are address lookup tables the caller wants the
+    // endpoint to resolve the destination address/options payload against,
+    // so large payloads don't exceed the legacy account limit.
+    let lookup_table_accounts: Vec<AccountInfo<'a>> = accounts_iter.cloned().collect();
+
     // Verify sender is signer
     if !sender_account.is_signer {
         return Err(SolanaOpenApiError::Unauthorized.into());
@@ -82,6 +87,8 @@ pub fn process<'a>(
         refund_address: *sender_account.key,
         executor_options: Vec::new(), // Default options
         receiver_options: Vec::new(), // Default options
+        use_onchain_quote: true,
+        lookup_table_accounts: lookup_table_accounts.iter().map(|a| *a.key).collect(),
     };
 
     // Create the cross-chain message
@@ -128,6 +135,7 @@ pub fn process<'a>(
         layerzero_endpoint,
         fee_account,
         sender_account,
+        &lookup_table_accounts,
         &message,
         send_data.destination_address,
     )?;
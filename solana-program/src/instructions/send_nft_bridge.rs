@@ -0,0 +1,208 @@
+/**
+ * @file instructions/send_nft_bridge.rs
+ * @description Instruction handler for locking or burning an NFT on the source chain before bridging it via LayerZero V2
+ */
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+    program_error::ProgramError,
+    pubkey,
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
+    msg,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::SolanaOpenApiError;
+use crate::layerzero::{CrossChainMessage, MessageOptions, send_to_endpoint};
+use crate::state::{MessageRecord, MessageType, ProgramConfig};
+
+/// The SPL Token program.
+const SPL_TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+const SPL_TOKEN_TRANSFER_IX: u8 = 3;
+const SPL_TOKEN_BURN_IX: u8 = 8;
+
+/// Whether the NFT is locked in a program-owned vault (so it can be unlocked if
+/// it comes back) or burned outright (so the destination side mints a fresh
+/// wrapped copy and there is nothing left to unlock).
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum NftBridgeMode {
+    Lock = 0,
+    Burn = 1,
+}
+
+/// Send-NFT-bridge instruction data
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct SendNftBridgeData {
+    pub destination_chain_id: u32,
+    pub destination_address: Vec<u8>,
+    pub collection: Vec<u8>,
+    pub token_uri: Vec<u8>,
+    pub mode: NftBridgeMode,
+}
+
+/// NFT-bridge payload carried as the `CrossChainMessage` payload, decoded on
+/// the destination chain to mint (or unlock) the corresponding NFT there.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct NftBridgePayload {
+    pub token_address: Pubkey,
+    pub collection: Vec<u8>,
+    pub token_uri: Vec<u8>,
+    pub mode: NftBridgeMode,
+}
+
+/// Process a send-NFT-bridge instruction: lock or burn the NFT on this chain,
+/// then relay a record of the move through the LayerZero V2 endpoint.
+pub fn process<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    // Get required accounts
+    let sender_account = next_account_info(accounts_iter)?;
+    let nft_mint = next_account_info(accounts_iter)?;
+    let nft_token_account = next_account_info(accounts_iter)?;
+    let vault_token_account = next_account_info(accounts_iter)?; // only used for Lock
+    let token_program = next_account_info(accounts_iter)?;
+    let message_account = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let layerzero_endpoint = next_account_info(accounts_iter)?;
+    let fee_account = next_account_info(accounts_iter)?;
+    let clock_sysvar = next_account_info(accounts_iter)?;
+
+    // Any remaining accounts are address lookup tables the caller wants the
+    // endpoint to resolve the destination address/options payload against,
+    // so large payloads don't exceed the legacy account limit.
+    let lookup_table_accounts: Vec<AccountInfo<'a>> = accounts_iter.cloned().collect();
+
+    // Verify sender is signer
+    if !sender_account.is_signer {
+        return Err(SolanaOpenApiError::Unauthorized.into());
+    }
+
+    if *token_program.key != SPL_TOKEN_PROGRAM_ID {
+        return Err(SolanaOpenApiError::InvalidAccountData.into());
+    }
+
+    // Deserialize the instruction data
+    let send_data = SendNftBridgeData::try_from_slice(instruction_data)
+        .map_err(|_| SolanaOpenApiError::InvalidInstructionData)?;
+
+    // Get program config
+    let config = ProgramConfig::try_from_slice(&config_account.data.borrow())
+        .map_err(|_| SolanaOpenApiError::InvalidAccountData)?;
+
+    if !config.is_initialized {
+        return Err(SolanaOpenApiError::AccountNotInitialized.into());
+    }
+    if config.layerzero_endpoint != *layerzero_endpoint.key {
+        return Err(SolanaOpenApiError::InvalidEndpoint.into());
+    }
+
+    // Lock the NFT in a program vault, or burn it outright, depending on `mode`.
+    // NFTs are zero-decimal, single-supply tokens, so the transfer/burn amount is always 1.
+    match send_data.mode {
+        NftBridgeMode::Lock => {
+            let instruction = Instruction {
+                program_id: *token_program.key,
+                accounts: vec![
+                    AccountMeta::new(*nft_token_account.key, false),
+                    AccountMeta::new(*vault_token_account.key, false),
+                    AccountMeta::new_readonly(*sender_account.key, true),
+                ],
+                data: [&[SPL_TOKEN_TRANSFER_IX][..], &1u64.to_le_bytes()].concat(),
+            };
+            invoke(
+                &instruction,
+                &[nft_token_account.clone(), vault_token_account.clone(), sender_account.clone()],
+            )?;
+        }
+        NftBridgeMode::Burn => {
+            let instruction = Instruction {
+                program_id: *token_program.key,
+                accounts: vec![
+                    AccountMeta::new(*nft_token_account.key, false),
+                    AccountMeta::new(*nft_mint.key, false),
+                    AccountMeta::new_readonly(*sender_account.key, true),
+                ],
+                data: [&[SPL_TOKEN_BURN_IX][..], &1u64.to_le_bytes()].concat(),
+            };
+            invoke(
+                &instruction,
+                &[nft_token_account.clone(), nft_mint.clone(), sender_account.clone()],
+            )?;
+        }
+    }
+
+    // Get current timestamp and nonce
+    let clock = Clock::from_account_info(clock_sysvar)?;
+    let timestamp = clock.unix_timestamp as u64;
+    let nonce = timestamp;
+
+    // Build the cross-chain payload describing the NFT that just moved
+    let payload = NftBridgePayload {
+        token_address: *nft_mint.key,
+        collection: send_data.collection.clone(),
+        token_uri: send_data.token_uri.clone(),
+        mode: send_data.mode,
+    };
+    let mut payload_bytes = Vec::new();
+    payload
+        .serialize(&mut payload_bytes)
+        .map_err(|_| SolanaOpenApiError::InvalidInstructionData)?;
+
+    let options = MessageOptions {
+        gas_limit: 0,
+        refund_address: *sender_account.key,
+        executor_options: Vec::new(),
+        receiver_options: Vec::new(),
+        use_onchain_quote: false,
+        lookup_table_accounts: lookup_table_accounts.iter().map(|a| *a.key).collect(),
+    };
+
+    let message = CrossChainMessage::new(
+        config.solana_chain_id,
+        send_data.destination_chain_id,
+        MessageType::NFTData as u8,
+        payload_bytes,
+        nonce,
+        Some(options),
+    );
+
+    // Generate message ID and record it
+    let message_id = message.generate_id();
+    let message_record = MessageRecord::new(
+        message_id,
+        config.solana_chain_id,
+        send_data.destination_chain_id,
+        MessageType::NFTData as u8,
+        *sender_account.key,
+        timestamp,
+    );
+    message_record
+        .serialize(&mut *message_account.data.borrow_mut())
+        .map_err(|_| ProgramError::AccountDataTooSmall)?;
+
+    // Relay the move to the destination chain via LayerZero V2
+    send_to_endpoint(
+        program_id,
+        layerzero_endpoint,
+        fee_account,
+        sender_account,
+        &lookup_table_accounts,
+        &message,
+        send_data.destination_address,
+    )?;
+
+    msg!("NFT bridged out successfully via LayerZero V2");
+    msg!("Message ID: {:?}", message_id);
+    msg!("Mode: {:?}", send_data.mode);
+    msg!("Destination Chain: {}", send_data.destination_chain_id);
+
+    Ok(())
+}
@@ -7,10 +7,15 @@
 use solana_program::{
     account_info::AccountInfo,
     entrypoint::ProgramResult,
+    keccak,
     msg,
-    program::invoke,
+    program::{invoke, invoke_signed},
     program_error::ProgramError,
     pubkey::Pubkey,
+    secp256k1_recover::secp256k1_recover,
+    rent::Rent,
+    sysvar::Sysvar,
+    system_instruction,
 };
 use borsh::{BorshDeserialize, BorshSerialize};
 use sha2::{Sha256, Digest};
@@ -18,6 +23,7 @@ use sha2::{Sha256, Digest};
 // All other imports have been removed as they were unused
 
 use crate::error::SolanaOpenApiError;
+use crate::state::{GuardianSet, MessageType, RegisteredEmitter};
 
 /// LayerZero V2 Endpoint Interface
 #[allow(dead_code)]
@@ -54,6 +60,19 @@ pub struct MessageOptions {
     pub refund_address: Pubkey,
     pub executor_options: Vec<u8>,
     pub receiver_options: Vec<u8>,
+    /// When set, `get_fee_quote` asks the LayerZero endpoint for a live quote
+    /// via CPI instead of the static fallback table, unless the endpoint
+    /// account is owned by the system program, in which case the table is used.
+    pub use_onchain_quote: bool,
+    /// Address Lookup Tables the sender expects the outer (versioned)
+    /// transaction to have loaded. These aren't resolved here -- a CPI always
+    /// operates on `AccountInfo`s the runtime already loaded for the outer
+    /// transaction, regardless of how it got them onto the wire -- but they're
+    /// carried through to the endpoint program so it can size its own account
+    /// list (e.g. executor/receiver config accounts) against the same tables
+    /// instead of requiring every one of them inlined in the CPI's account
+    /// metas.
+    pub lookup_table_accounts: Vec<Pubkey>,
 }
 
 /// Cross-chain message structure
@@ -67,6 +86,45 @@ pub struct CrossChainMessage {
     pub options: Option<MessageOptions>,
 }
 
+/// A fungible token transfer with an attached arbitrary payload, modeled on
+/// Wormhole's token-bridge "transfer with payload". Carried as the Borsh
+/// encoding of `CrossChainMessage.payload` when `message_type` is
+/// `MessageType::TokenTransfer`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct TokenTransferPayload {
+    pub amount: u128,
+    pub token_address: [u8; 32],
+    pub token_chain: u16,
+    pub recipient: [u8; 32],
+    pub recipient_chain: u16,
+    pub sender: [u8; 32],
+    pub app_payload: Vec<u8>,
+}
+
+/// An NFT transfer with an attached arbitrary payload, modeled on Wormhole's
+/// NFT-bridge transfer layout. Carried as the Borsh encoding of
+/// `CrossChainMessage.payload` when `message_type` is `MessageType::NFTData`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct NftTransferPayload {
+    pub token_address: [u8; 32],
+    pub token_chain: u16,
+    pub token_id: [u8; 32],
+    pub uri: Vec<u8>,
+    pub recipient: [u8; 32],
+    pub recipient_chain: u16,
+    pub app_payload: Vec<u8>,
+}
+
+/// A `CrossChainMessage` payload decoded according to its `message_type`.
+#[derive(Debug, Clone)]
+pub enum DecodedPayload {
+    TokenTransfer(TokenTransferPayload),
+    NftTransfer(NftTransferPayload),
+    /// Message types with no structured payload encoding (yet); the raw
+    /// bytes are handed back unchanged.
+    Opaque(Vec<u8>),
+}
+
 impl CrossChainMessage {
     /// Create a new cross-chain message
     pub fn new(
@@ -126,6 +184,69 @@ impl CrossChainMessage {
         
         Ok(base_fee + byte_fee)
     }
+
+    /// Create a new cross-chain message carrying a `TokenTransferPayload`.
+    pub fn new_token_transfer(
+        source_chain_id: u32,
+        destination_chain_id: u32,
+        transfer: &TokenTransferPayload,
+        nonce: u64,
+        options: Option<MessageOptions>,
+    ) -> Result<Self, ProgramError> {
+        let mut payload = Vec::new();
+        transfer
+            .serialize(&mut payload)
+            .map_err(|_| SolanaOpenApiError::InvalidInstructionData)?;
+
+        Ok(Self::new(
+            source_chain_id,
+            destination_chain_id,
+            MessageType::TokenTransfer as u8,
+            payload,
+            nonce,
+            options,
+        ))
+    }
+
+    /// Create a new cross-chain message carrying an `NftTransferPayload`.
+    pub fn new_nft_transfer(
+        source_chain_id: u32,
+        destination_chain_id: u32,
+        transfer: &NftTransferPayload,
+        nonce: u64,
+        options: Option<MessageOptions>,
+    ) -> Result<Self, ProgramError> {
+        let mut payload = Vec::new();
+        transfer
+            .serialize(&mut payload)
+            .map_err(|_| SolanaOpenApiError::InvalidInstructionData)?;
+
+        Ok(Self::new(
+            source_chain_id,
+            destination_chain_id,
+            MessageType::NFTData as u8,
+            payload,
+            nonce,
+            options,
+        ))
+    }
+
+    /// Decode `payload` according to `message_type`, returning a typed
+    /// `TokenTransferPayload`/`NftTransferPayload` for the transfer message
+    /// types and the raw bytes for anything else.
+    pub fn decode_payload(&self) -> Result<DecodedPayload, ProgramError> {
+        if self.message_type == MessageType::TokenTransfer as u8 {
+            let transfer = TokenTransferPayload::try_from_slice(&self.payload)
+                .map_err(|_| SolanaOpenApiError::InvalidInstructionData)?;
+            Ok(DecodedPayload::TokenTransfer(transfer))
+        } else if self.message_type == MessageType::NFTData as u8 {
+            let transfer = NftTransferPayload::try_from_slice(&self.payload)
+                .map_err(|_| SolanaOpenApiError::InvalidInstructionData)?;
+            Ok(DecodedPayload::NftTransfer(transfer))
+        } else {
+            Ok(DecodedPayload::Opaque(self.payload.clone()))
+        }
+    }
 }
 
 /// LayerZero V2 Endpoint Instructions
@@ -159,21 +280,25 @@ pub fn send_to_endpoint<'a>(
     endpoint_account: &'a AccountInfo<'a>,
     fee_account: &'a AccountInfo<'a>,
     sender_account: &'a AccountInfo<'a>,
+    lookup_table_accounts: &'a [AccountInfo<'a>],
     message: &CrossChainMessage,
     destination_address: Vec<u8>,
 ) -> ProgramResult {
     msg!("Sending message to LayerZero V2 endpoint");
-    
+
     // Ensure the message has options
     let options = message.options.as_ref().ok_or(SolanaOpenApiError::InvalidMessageOptions)?;
-    
+
     // Log message details
     msg!("Source chain: {}", message.source_chain_id);
     msg!("Destination chain: {}", message.destination_chain_id);
     msg!("Message type: {}", message.message_type);
     msg!("Payload length: {}", message.payload.len());
     msg!("Nonce: {}", message.nonce);
-    
+    if !options.lookup_table_accounts.is_empty() {
+        msg!("Lookup tables referenced: {}", options.lookup_table_accounts.len());
+    }
+
     // Create the instruction data
     let instruction_data = LayerZeroInstruction::Send {
         destination_chain_id: message.destination_chain_id,
@@ -181,71 +306,327 @@ pub fn send_to_endpoint<'a>(
         payload: message.payload.clone(),
         options: options.clone(),
     };
-    
+
     // Serialize the instruction data
     let mut data = Vec::new();
     instruction_data.serialize(&mut data).map_err(|_| ProgramError::InvalidInstructionData)?;
-    
-    // Create the instruction
+
+    // Create the instruction. Lookup-table accounts are appended as readonly,
+    // non-signer metas: the CPI itself doesn't shrink because of them (the
+    // runtime already handed us fully-resolved `AccountInfo`s), but the
+    // endpoint program can use their presence to validate or look up extra
+    // per-destination config without those accounts being inlined individually.
+    let mut accounts = vec![
+        solana_program::instruction::AccountMeta::new(*endpoint_account.key, false),
+        solana_program::instruction::AccountMeta::new(*fee_account.key, false),
+        solana_program::instruction::AccountMeta::new(*sender_account.key, true),
+    ];
+    accounts.extend(
+        lookup_table_accounts
+            .iter()
+            .map(|account| solana_program::instruction::AccountMeta::new_readonly(*account.key, false)),
+    );
     let instruction = solana_program::instruction::Instruction {
         program_id: *endpoint_account.owner,
-        accounts: vec![
-            solana_program::instruction::AccountMeta::new(*endpoint_account.key, false),
-            solana_program::instruction::AccountMeta::new(*fee_account.key, false),
-            solana_program::instruction::AccountMeta::new(*sender_account.key, true),
-        ],
+        accounts,
         data,
     };
-    
+
     // The accounts array needs AccountInfo<'_> elements, not &AccountInfo<'_>
-    let account_infos = [endpoint_account.clone(), fee_account.clone(), sender_account.clone()];
+    let mut account_infos = vec![endpoint_account.clone(), fee_account.clone(), sender_account.clone()];
+    account_infos.extend(lookup_table_accounts.iter().cloned());
     invoke(
         &instruction,
         &account_infos,
     )?;
-    
+
     msg!("Message sent successfully to LayerZero V2 endpoint");
     Ok(())
 }
 
-/// Verify a message from the LayerZero endpoint
-pub fn verify_from_endpoint(
-    _program_id: &Pubkey,
+/// A single guardian attestation over a message body: the guardian's index
+/// into the active guardian set, and a standard 65-byte secp256k1 signature
+/// (`r || s || recovery_id`).
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct GuardianSignature {
+    pub index: u8,
+    pub signature: [u8; 65],
+}
+
+/// Verify that `signatures` reach guardian quorum over `body`, modeled on
+/// Wormhole's VAA signature verification: the digest is the double-keccak256
+/// of the body, signatures must be provided in strictly increasing guardian
+/// index order (no duplicates), and quorum is `floor(2/3 * N) + 1`.
+pub(crate) fn verify_guardian_signatures(
+    guardian_set: &GuardianSet,
+    body: &[u8],
+    signatures: &[GuardianSignature],
+) -> ProgramResult {
+    let digest = keccak::hash(&keccak::hash(body).to_bytes()).to_bytes();
+
+    let quorum = guardian_set.keys.len() * 2 / 3 + 1;
+    let mut last_index: i16 = -1;
+    let mut valid_count: usize = 0;
+
+    for sig in signatures {
+        // Guardian indices must be strictly increasing to rule out duplicates.
+        if i16::from(sig.index) <= last_index {
+            return Err(SolanaOpenApiError::Unauthorized.into());
+        }
+        last_index = i16::from(sig.index);
+
+        let guardian_key = guardian_set
+            .keys
+            .get(sig.index as usize)
+            .ok_or(SolanaOpenApiError::Unauthorized)?;
+
+        let recovery_id = sig.signature[64];
+        let recovered_pubkey = secp256k1_recover(&digest, recovery_id, &sig.signature[0..64])
+            .map_err(|_| SolanaOpenApiError::Unauthorized)?;
+
+        // Guardian addresses, like Ethereum addresses, are the last 20 bytes
+        // of the keccak256 hash of the uncompressed public key.
+        let recovered_address = &keccak::hash(&recovered_pubkey.to_bytes()).to_bytes()[12..32];
+        if recovered_address != guardian_key {
+            return Err(SolanaOpenApiError::Unauthorized.into());
+        }
+
+        valid_count += 1;
+    }
+
+    if valid_count >= quorum {
+        Ok(())
+    } else {
+        Err(SolanaOpenApiError::GuardianQuorumNotMet.into())
+    }
+}
+
+/// Wormhole VAA header: protocol version, the guardian set the signatures
+/// were made against, and the guardian attestations themselves.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct VaaHeader {
+    pub version: u8,
+    pub guardian_set_index: u32,
+    pub signatures: Vec<GuardianSignature>,
+}
+
+/// Wormhole VAA body. This is the payload guardians actually sign (as
+/// `keccak256(keccak256(body))`) and carries the emitter identity
+/// (`emitter_chain`, `emitter_address`, `sequence`) used for both the
+/// registered-emitter allowlist check and replay protection.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct VaaBody {
+    pub timestamp: u32,
+    pub nonce: u32,
+    pub emitter_chain: u16,
+    pub emitter_address: [u8; 32],
+    pub sequence: u64,
+    pub consistency_level: u8,
+    pub payload: Vec<u8>,
+}
+
+/// A full Wormhole Verified Action Approval: guardian attestations plus the
+/// body they attest to.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Vaa {
+    pub header: VaaHeader,
+    pub body: VaaBody,
+}
+
+/// Verify a full Wormhole VAA against the active guardian set and the
+/// program's registered-emitter allowlist: guardian-set identity and expiry,
+/// that the emitter is allowlisted, and that guardian signatures over the
+/// body reach quorum. Returns the parsed body on success.
+pub fn verify_vaa(
+    guardian_set: &GuardianSet,
+    registered_emitters: &[RegisteredEmitter],
+    current_timestamp: u64,
+    vaa: &Vaa,
+) -> Result<VaaBody, ProgramError> {
+    if vaa.header.guardian_set_index != guardian_set.index {
+        return Err(SolanaOpenApiError::InvalidEndpoint.into());
+    }
+    if guardian_set.expiration_time != 0 && current_timestamp > guardian_set.expiration_time {
+        return Err(SolanaOpenApiError::GuardianSetExpired.into());
+    }
+
+    let is_registered_emitter = registered_emitters
+        .iter()
+        .any(|e| e.chain == vaa.body.emitter_chain && e.address == vaa.body.emitter_address);
+    if !is_registered_emitter {
+        return Err(SolanaOpenApiError::Unauthorized.into());
+    }
+
+    let mut body_bytes = Vec::new();
+    vaa.body
+        .serialize(&mut body_bytes)
+        .map_err(|_| SolanaOpenApiError::InvalidInstructionData)?;
+    verify_guardian_signatures(guardian_set, &body_bytes, &vaa.header.signatures)?;
+
+    Ok(vaa.body.clone())
+}
+
+/// Claim seed prefix for the per-message replay-protection PDA.
+const CLAIM_SEED: &[u8] = b"claim";
+
+/// Derive, create, and populate the claim PDA for `message`, rejecting the
+/// call if the claim already exists. Modeled on Wormhole's claimable-VAA
+/// design: the claim account is a zero-data, rent-exempt PDA derived from
+/// `[b"claim", message.generate_id()]`, so a relayer delivering the same
+/// message twice finds the account already created the second time.
+pub fn claim_message<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    claim_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    message: &CrossChainMessage,
+) -> ProgramResult {
+    let message_id = message.generate_id();
+    let (claim_pda, bump) = Pubkey::find_program_address(&[CLAIM_SEED, &message_id], program_id);
+    if claim_pda != *claim_account.key {
+        return Err(SolanaOpenApiError::InvalidAccountData.into());
+    }
+
+    // The claim holds no data, so its existence shows in its rent lamports.
+    if claim_account.lamports() > 0 {
+        return Err(SolanaOpenApiError::MessageAlreadyProcessed.into());
+    }
+
+    let rent = Rent::get()?;
+    let lamports = rent.minimum_balance(0);
+
+    invoke_signed(
+        &system_instruction::create_account(payer.key, claim_account.key, lamports, 0, program_id),
+        &[payer.clone(), claim_account.clone(), system_program.clone()],
+        &[&[CLAIM_SEED, &message_id, &[bump]]],
+    )?;
+
+    msg!("Claimed message {:?}", message_id);
+    Ok(())
+}
+
+/// Canonical preimage guardians attest to for a LayerZero V2 message: binds
+/// the full routing identity (both chain IDs, message type, nonce) and
+/// payload, plus whatever extra `channel_binding` bytes the caller supplies
+/// (e.g. a response's `src_eid`/`sender` channel and the `original_message_id`
+/// it's meant to complete), so a signature made over one message can't be
+/// replayed against a different chain, type, nonce, channel, or target.
+#[derive(BorshSerialize)]
+struct EndpointAttestation<'a> {
+    source_chain_id: u32,
+    destination_chain_id: u32,
+    message_type: u8,
+    nonce: u64,
+    payload: &'a [u8],
+    channel_binding: &'a [u8],
+}
+
+/// Verify a message from the LayerZero endpoint against the active guardian
+/// set, rejecting messages that don't reach guardian quorum, and atomically
+/// claim it so the same message can never be verified twice. `channel_binding`
+/// is folded into the signed digest alongside `message`'s own fields -- pass
+/// `&[]` when the message's chain IDs/type/nonce are the whole routing
+/// identity, or extra context bytes (e.g. channel + target IDs) when they
+/// aren't.
+pub fn verify_from_endpoint<'a>(
+    program_id: &Pubkey,
     _endpoint_account: &AccountInfo,
     message: &CrossChainMessage,
+    channel_binding: &[u8],
+    guardian_set: &GuardianSet,
+    guardian_set_index: u32,
+    current_timestamp: u64,
+    signatures: &[GuardianSignature],
+    payer: &AccountInfo<'a>,
+    claim_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
 ) -> ProgramResult {
     msg!("Verifying message from LayerZero V2 endpoint");
-    
-    // Verify code removed to avoid references to undefined variables
-    // This will be implemented properly in production code
-    
+
     // Log message details
     msg!("Source chain: {}", message.source_chain_id);
     msg!("Destination chain: {}", message.destination_chain_id);
     msg!("Message type: {}", message.message_type);
     msg!("Payload length: {}", message.payload.len());
     msg!("Nonce: {}", message.nonce);
-    
-    // In a production environment, we would verify the message signature
-    // and validate that it came from the expected source chain and address
-    
+
+    if guardian_set_index != guardian_set.index {
+        return Err(SolanaOpenApiError::InvalidEndpoint.into());
+    }
+
+    if guardian_set.expiration_time != 0 && current_timestamp > guardian_set.expiration_time {
+        return Err(SolanaOpenApiError::GuardianSetExpired.into());
+    }
+
+    let attestation = EndpointAttestation {
+        source_chain_id: message.source_chain_id,
+        destination_chain_id: message.destination_chain_id,
+        message_type: message.message_type,
+        nonce: message.nonce,
+        payload: &message.payload,
+        channel_binding,
+    };
+    let mut attestation_bytes = Vec::new();
+    attestation
+        .serialize(&mut attestation_bytes)
+        .map_err(|_| SolanaOpenApiError::InvalidInstructionData)?;
+    verify_guardian_signatures(guardian_set, &attestation_bytes, signatures)?;
+
+    claim_message(program_id, payer, claim_account, system_program, message)?;
+
     msg!("Message verified successfully from LayerZero V2 endpoint");
     Ok(())
 }
 
 /// Get quote for sending a cross-chain message
-pub fn get_fee_quote(
+pub fn get_fee_quote<'a>(
     _program_id: &Pubkey,
-    _endpoint_account: &AccountInfo,
+    endpoint_account: &'a AccountInfo<'a>,
     destination_chain_id: u32,
     payload_size: usize,
     options: &MessageOptions,
 ) -> Result<u64, ProgramError> {
     msg!("Getting fee quote from LayerZero V2 endpoint");
-    
-    // In a production environment, this would make a CPI call to the LayerZero endpoint
-    // to get an accurate fee quote. For now, we'll use our estimation function.
-    
+
+    // The static table below only stands in for the endpoint's own quote when
+    // there's no real endpoint program to ask, e.g. a system-owned account in
+    // tests; otherwise defer to the authoritative on-chain quote.
+    let has_real_endpoint = *endpoint_account.owner != solana_program::system_program::ID;
+    if options.use_onchain_quote && has_real_endpoint {
+        let instruction_data = LayerZeroInstruction::QuoteFee {
+            destination_chain_id,
+            payload_size: payload_size as u64,
+            options: options.clone(),
+        };
+
+        let mut data = Vec::new();
+        instruction_data.serialize(&mut data).map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        let instruction = solana_program::instruction::Instruction {
+            program_id: *endpoint_account.owner,
+            accounts: vec![solana_program::instruction::AccountMeta::new_readonly(*endpoint_account.key, false)],
+            data,
+        };
+
+        invoke(&instruction, &[endpoint_account.clone()])?;
+
+        let (return_program_id, return_data) =
+            solana_program::program::get_return_data().ok_or(SolanaOpenApiError::InvalidEndpoint)?;
+        if return_program_id != *endpoint_account.owner {
+            return Err(SolanaOpenApiError::InvalidEndpoint.into());
+        }
+        let fee_bytes: [u8; 8] = return_data
+            .get(0..8)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(SolanaOpenApiError::InvalidInstructionData)?;
+        let fee = u64::from_le_bytes(fee_bytes);
+
+        msg!("On-chain fee quote: {} lamports", fee);
+        return Ok(fee);
+    }
+
+    // Fallback static table used when no on-chain quote is requested or
+    // available.
     let base_fee = match destination_chain_id {
         1 => 1_000_000, // Ethereum (in lamports, 0.001 SOL)
         2 => 500_000,   // Arbitrum (in lamports, 0.0005 SOL)
@@ -253,13 +634,13 @@ pub fn get_fee_quote(
         4 => 400_000,   // Polygon (in lamports, 0.0004 SOL)
         _ => 800_000,   // Default (in lamports, 0.0008 SOL)
     };
-    
+
     // Calculate fee based on payload size and gas limit
     let byte_fee = (payload_size as u64) * 100; // 100 lamports per byte
     let gas_fee = options.gas_limit / 1000; // Simplified gas fee calculation
-    
+
     let total_fee = base_fee + byte_fee + gas_fee;
     msg!("Estimated fee: {} lamports", total_fee);
-    
+
     Ok(total_fee)
 }
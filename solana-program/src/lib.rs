@@ -43,6 +43,8 @@ pub fn process_instruction(
         1 => receive_cross_chain_message(program_id, accounts, &instruction_data[1..]),
         2 => query_cross_chain_data(program_id, accounts, &instruction_data[1..]),
         3 => process_cross_chain_response(program_id, accounts, &instruction_data[1..]),
+        4 => process_wormhole_vaa(program_id, accounts, &instruction_data[1..]),
+        5 => send_nft_bridge(program_id, accounts, &instruction_data[1..]),
         _ => Err(ProgramError::InvalidInstructionData),
     }
 }
@@ -82,3 +84,21 @@ fn process_cross_chain_response(
 ) -> ProgramResult {
     instructions::process_response::process(program_id, accounts, instruction_data)
 }
+
+/// Settle a Wormhole VAA alongside LayerZero-based messages
+fn process_wormhole_vaa(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    instructions::process_vaa::process(program_id, accounts, instruction_data)
+}
+
+/// Lock or burn an NFT on this chain and relay a record of the move via LayerZero
+fn send_nft_bridge(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    instructions::send_nft_bridge::process(program_id, accounts, instruction_data)
+}
@@ -32,6 +32,27 @@ pub enum MessageStatus {
     Completed = 5,
 }
 
+/// Guardian set used to verify cross-chain message attestations, modeled on
+/// Wormhole's guardian-quorum design: an ordered set of 20-byte secp256k1
+/// guardian addresses, the set's own index, and an optional expiry.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct GuardianSet {
+    pub index: u32,
+    pub keys: Vec<[u8; 20]>,
+    /// Unix timestamp after which this set is no longer valid. `0` means the
+    /// set never expires (the currently active set).
+    pub expiration_time: u64,
+}
+
+/// A single emitter allowed to originate Wormhole VAAs accepted by this
+/// program, identified the same way Wormhole does: a chain ID plus a 32-byte
+/// emitter address on that chain.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct RegisteredEmitter {
+    pub chain: u16,
+    pub address: [u8; 32],
+}
+
 /// Program configuration account data
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub struct ProgramConfig {
@@ -40,6 +61,10 @@ pub struct ProgramConfig {
     pub layerzero_endpoint: Pubkey,
     pub fee_account: Pubkey,
     pub solana_chain_id: u32,
+    pub guardian_set: GuardianSet,
+    /// Emitters this program will accept Wormhole VAAs from; VAAs from any
+    /// other `(emitter_chain, emitter_address)` pair are rejected.
+    pub registered_emitters: Vec<RegisteredEmitter>,
 }
 
 impl IsInitialized for ProgramConfig {
@@ -109,6 +134,42 @@ impl MessageRecord {
     }
 }
 
+/// Replay-protection marker for an inbound Wormhole VAA, stored in a PDA
+/// derived from `(emitter_chain, emitter_address, sequence)` so the same VAA
+/// can never be settled twice.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ReplayProtection {
+    pub is_initialized: bool,
+    pub emitter_chain: u16,
+    pub emitter_address: [u8; 32],
+    pub sequence: u64,
+}
+
+impl IsInitialized for ReplayProtection {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+/// Per-channel inbound nonce tracking for LayerZero's lazy-inbound-nonce
+/// model: a high-water mark of the highest contiguously-executed nonce for
+/// this `(src_eid, sender)` channel, plus a bitmap of nonces executed out of
+/// order above that mark so the mark can advance once gaps fill.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct InboundNonceTracker {
+    pub is_initialized: bool,
+    pub src_eid: u32,
+    pub sender: [u8; 32],
+    pub inbound_nonce: u64,
+    pub executed_bitmap: u64,
+}
+
+impl IsInitialized for InboundNonceTracker {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
 /// Cross-chain query parameters
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub struct QueryParams {
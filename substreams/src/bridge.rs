@@ -1,22 +1,52 @@
 use substreams::errors::Error;
+use substreams::store::StoreGetProto;
 use substreams_solana::pb::sf::solana::r#type::v1::Block;
 use substreams_solana::pb::sf::solana::r#type::v1::ConfirmedTransaction;
 
-// Re-export the protobuf types
-pub use crate::pb::bridge::v1::*;
+use std::collections::HashMap;
+
+use crate::marketplace::{MAGIC_EDEN_PROGRAM_ID, TENSOR_PROGRAM_ID};
+use crate::utils::{
+    build_alt_cache, compute_fee_percentiles, extract_compute_fee_info, resolve_account_keys,
+    writable_account_mask, AltCache, AltTable,
+};
 
 // Known bridge program IDs on Solana
 const WORMHOLE_PROGRAM_ID: &str = "worm2ZoG2kUd4vFXhvjh93UUH596ayRfgQ2MgjNMTth";
 const LAYERZERO_PROGRAM_ID: &str = "lzTVa7cYDdt7R5bLsQrp4HgQCy5eRt7Mdu5PK4WsP8Q";
+const WORMHOLE_NFT_BRIDGE_PROGRAM_ID: &str = "WnFt12ZrnzZrFZkt2xsNsaNWoQribnuQ5B5FrDbwDhD";
+
+/// Native token-bridge instruction tags: 4/5/10/11 are outbound transfer
+/// variants (`Transfer`/`TransferWithPayload` and their wrapped-token
+/// counterparts), 2/3/8/9 are inbound complete (redeem) variants.
+const WORMHOLE_SEND_TAGS: [u8; 4] = [4, 5, 10, 11];
+const WORMHOLE_RECEIVE_TAGS: [u8; 4] = [2, 3, 8, 9];
+
+/// Account index, within a `Complete*` instruction's account list, of the
+/// posted-VAA account the transfer is redeemed from (per the publicly
+/// documented Token Bridge account order: payer, config, **vaa**, claim, ...).
+/// That layout isn't vendored in this sandbox to check byte-for-byte, so this
+/// is a best-effort position, not a verified one.
+const WORMHOLE_VAA_ACCOUNT_INDEX: usize = 2;
 
-pub fn extract_bridge_events(block: Block) -> Result<BridgeEvents, Error> {
+/// Native NFT-bridge instruction tags: 4/5 are outbound transfer variants
+/// (TransferWrapped/TransferNative), 1/2 are inbound complete (redeem)
+/// variants (CompleteNative/CompleteWrapped).
+const WORMHOLE_NFT_IX_TRANSFER_WRAPPED: u8 = 4;
+const WORMHOLE_NFT_IX_TRANSFER_NATIVE: u8 = 5;
+const WORMHOLE_NFT_IX_COMPLETE_NATIVE: u8 = 1;
+const WORMHOLE_NFT_IX_COMPLETE_WRAPPED: u8 = 2;
+
+pub fn extract_bridge_events(block: Block, alt_store: StoreGetProto<AltTable>) -> Result<BridgeEvents, Error> {
     let mut events = BridgeEvents { events: vec![] };
     let block_number = block.slot;
     let block_hash = block.blockhash.clone();
     let timestamp = block.block_time.as_ref().map(|t| t.timestamp).unwrap_or(0) as u64;
+    let alt_cache = build_alt_cache(&block);
+    let decoders = bridge_decoders();
 
     for transaction in block.transactions {
-        if let Some(bridge_event) = process_transaction(transaction, block_number, &block_hash, timestamp) {
+        if let Some(bridge_event) = process_transaction(transaction, block_number, &block_hash, timestamp, &alt_cache, &alt_store, &decoders) {
             events.events.push(bridge_event);
         }
     }
@@ -29,6 +59,9 @@ fn process_transaction(
     block_number: u64,
     block_hash: &str,
     timestamp: u64,
+    alt_cache: &AltCache,
+    alt_store: &StoreGetProto<AltTable>,
+    decoders: &[Box<dyn BridgeProtocolDecoder>],
 ) -> Option<BridgeEvent> {
     // Skip failed transactions
     if !transaction.meta.as_ref()?.status.unwrap_or_default().err.is_none() {
@@ -36,91 +69,539 @@ fn process_transaction(
     }
 
     let transaction_hash = bs58::encode(&transaction.transaction.as_ref()?.signatures[0]).into_string();
-    
-    // Look for bridge program invocations
-    for account_key in &transaction.transaction.as_ref()?.message.as_ref()?.account_keys {
-        if account_key == WORMHOLE_PROGRAM_ID || account_key == LAYERZERO_PROGRAM_ID {
-            // This is a transaction involving a bridge
-            // In a real implementation, we would parse the instruction data and logs
-            // to determine the exact event type and extract relevant information
-            
-            // For this example, we'll create a simplified bridge event
-            let bridge_protocol = determine_bridge_protocol(account_key);
-            let event_type = determine_bridge_event_type(&transaction);
-            let (source_chain, destination_chain) = extract_chain_info(&transaction);
-            let (sender_address, receiver_address) = extract_sender_receiver_addresses(&transaction);
-            let (token_address, token_id, amount) = extract_token_info(&transaction);
-            let (fee, nonce, message_hash) = extract_bridge_details(&transaction);
-            
-            return Some(BridgeEvent {
-                id: format!("{}-{}", transaction_hash, 0),
-                transaction_hash,
-                block_number,
-                block_hash: block_hash.to_string(),
-                timestamp,
-                bridge_protocol,
-                event_type,
-                source_chain,
-                destination_chain,
-                sender_address,
-                receiver_address,
-                token_address,
-                token_id,
-                amount,
-                fee,
-                nonce,
-                message_hash,
+
+    // Resolve the full account list, including any accounts pulled in through
+    // address lookup tables on v0 (versioned) transactions, so bridge invocations
+    // that rely on ALTs aren't silently missed. Legacy transactions fall straight
+    // through to their static `account_keys`.
+    let resolved_accounts = resolve_account_keys(&transaction, alt_cache, alt_store);
+    let message = transaction.transaction.as_ref()?.message.as_ref()?;
+
+    // Walk the compiled instructions looking for one whose program matches a
+    // registered bridge decoder. New bridge programs are supported by adding a
+    // `BridgeProtocolDecoder` impl to `bridge_decoders()`, not by editing this loop.
+    for instruction in &message.instructions {
+        let Some(program_key) = resolved_accounts.get(instruction.program_id_index as usize) else {
+            continue;
+        };
+
+        let Some(decoder) = decoders.iter().find(|d| d.program_id() == program_key) else {
+            continue;
+        };
+
+        let Some(decoded) = decoder.decode(&instruction.data, &instruction.accounts, &resolved_accounts) else {
+            continue;
+        };
+
+        let fee_info = extract_compute_fee_info(&transaction, &resolved_accounts);
+
+        return Some(BridgeEvent {
+            id: format!("{}-{}", transaction_hash, 0),
+            transaction_hash,
+            block_number,
+            block_hash: block_hash.to_string(),
+            timestamp,
+            bridge_protocol: decoder.protocol_name().to_string(),
+            event_type: decoded.event_type,
+            source_chain: decoded.source_chain,
+            destination_chain: decoded.destination_chain,
+            sender_address: decoded.sender_address,
+            receiver_address: decoded.receiver_address,
+            token_address: decoded.token_address,
+            token_id: decoded.token_id,
+            amount: decoded.amount,
+            fee: fee_info.total_fee.to_string(),
+            nonce: decoded.nonce,
+            message_hash: decoded.message_hash,
+            cu_requested: fee_info.cu_requested,
+            cu_consumed: fee_info.cu_consumed,
+            priority_fee_micro_lamports: fee_info.priority_fee_micro_lamports,
+            total_fee: fee_info.total_fee,
+        });
+    }
+
+    None
+}
+
+/// Bridge event fields recovered from a protocol's raw instruction payload.
+#[derive(Debug, Clone, Default)]
+struct DecodedBridgeEvent {
+    event_type: String,
+    source_chain: String,
+    destination_chain: String,
+    sender_address: String,
+    receiver_address: String,
+    token_address: String,
+    token_id: String,
+    amount: String,
+    nonce: u64,
+    message_hash: String,
+}
+
+/// Decodes a single bridge program's instruction data into bridge event fields.
+/// Registering a new bridge program means adding an impl and listing it in
+/// `bridge_decoders()` -- `process_transaction` never needs to change.
+trait BridgeProtocolDecoder {
+    /// The program ID this decoder recognizes.
+    fn program_id(&self) -> &'static str;
+
+    /// Human-readable protocol name stored on the emitted `BridgeEvent`.
+    fn protocol_name(&self) -> &'static str;
+
+    /// Decode one instruction, returning `None` if it isn't a bridge transfer
+    /// instruction this decoder understands. `account_indexes` is the
+    /// instruction's own account-index list (`instruction.accounts`) and
+    /// `resolved_accounts` is the transaction's full resolved account-key
+    /// list, so a decoder can follow an account reference (e.g. a posted VAA
+    /// account) rather than only reading the instruction's inline data.
+    fn decode(
+        &self,
+        instruction_data: &[u8],
+        account_indexes: &[u8],
+        resolved_accounts: &[String],
+    ) -> Option<DecodedBridgeEvent>;
+}
+
+fn bridge_decoders() -> Vec<Box<dyn BridgeProtocolDecoder>> {
+    vec![
+        Box::new(WormholeDecoder),
+        Box::new(WormholeNftBridgeDecoder),
+        Box::new(LayerZeroDecoder),
+    ]
+}
+
+struct WormholeDecoder;
+
+impl BridgeProtocolDecoder for WormholeDecoder {
+    fn program_id(&self) -> &'static str {
+        WORMHOLE_PROGRAM_ID
+    }
+
+    fn protocol_name(&self) -> &'static str {
+        "wormhole"
+    }
+
+    fn decode(
+        &self,
+        instruction_data: &[u8],
+        account_indexes: &[u8],
+        resolved_accounts: &[String],
+    ) -> Option<DecodedBridgeEvent> {
+        let tag = *instruction_data.first()?;
+
+        if WORMHOLE_SEND_TAGS.contains(&tag) {
+            // Outbound: the transfer terms are the instruction's own args --
+            // no VAA exists yet at send time, since one is only produced once
+            // guardians sign the message this instruction emits.
+            let args = decode_wormhole_transfer_args(instruction_data)?;
+            return Some(DecodedBridgeEvent {
+                event_type: "send".to_string(),
+                source_chain: "solana".to_string(),
+                destination_chain: wormhole_chain_name(args.target_chain),
+                sender_address: String::new(),
+                receiver_address: bs58::encode(args.target_address).into_string(),
+                // The mint is referenced by account, not carried in the args;
+                // resolving it needs the real Token Bridge IDL's per-variant
+                // account order, which isn't pinned down here.
+                token_address: String::new(),
+                token_id: String::new(),
+                amount: args.amount.to_string(),
+                nonce: args.nonce as u64,
+                message_hash: String::new(),
+            });
+        }
+
+        if WORMHOLE_RECEIVE_TAGS.contains(&tag) {
+            // Inbound: the transfer terms live in the posted-VAA account this
+            // instruction redeems, not in its own args, and that account's
+            // on-chain data isn't available from block/transaction data
+            // alone. Report which VAA account was referenced rather than
+            // fabricating the amount/token/recipient it would otherwise hold.
+            let vaa_account = account_indexes
+                .get(WORMHOLE_VAA_ACCOUNT_INDEX)
+                .and_then(|&idx| resolved_accounts.get(idx as usize))
+                .cloned()
+                .unwrap_or_default();
+            return Some(DecodedBridgeEvent {
+                event_type: "receive".to_string(),
+                source_chain: "unknown".to_string(),
+                destination_chain: "solana".to_string(),
+                sender_address: String::new(),
+                receiver_address: String::new(),
+                token_address: String::new(),
+                token_id: String::new(),
+                amount: "0".to_string(),
+                nonce: 0,
+                message_hash: vaa_account,
             });
         }
+
+        None
     }
-    
-    None
 }
 
-fn determine_bridge_protocol(program_id: &str) -> String {
-    match program_id {
-        WORMHOLE_PROGRAM_ID => "wormhole".to_string(),
-        LAYERZERO_PROGRAM_ID => "layerzero".to_string(),
-        _ => "unknown".to_string(),
+/// Fixed leading fields of the Token Bridge's native `Transfer`/
+/// `TransferWithPayload` instruction args: nonce(4) + amount(8) + fee(8) +
+/// target_address(32) + target_chain(2), little-endian, immediately following
+/// the 1-byte instruction tag. Taken from the public Token Bridge Rust source;
+/// not vendored in this sandbox to check byte-for-byte, so best-effort.
+struct WormholeTransferArgs {
+    nonce: u32,
+    amount: u64,
+    target_address: [u8; 32],
+    target_chain: u16,
+}
+
+fn decode_wormhole_transfer_args(instruction_data: &[u8]) -> Option<WormholeTransferArgs> {
+    if instruction_data.len() < 1 + 4 + 8 + 8 + 32 + 2 {
+        return None;
+    }
+    let nonce = u32::from_le_bytes(instruction_data[1..5].try_into().ok()?);
+    let amount = u64::from_le_bytes(instruction_data[5..13].try_into().ok()?);
+    // `fee` at [13..21] isn't surfaced on `BridgeEvent`.
+    let target_address: [u8; 32] = instruction_data[21..53].try_into().ok()?;
+    let target_chain = u16::from_le_bytes(instruction_data[53..55].try_into().ok()?);
+    Some(WormholeTransferArgs { nonce, amount, target_address, target_chain })
+}
+
+/// Map a Wormhole numeric chain ID to its canonical chain name.
+fn wormhole_chain_name(chain_id: u16) -> String {
+    match chain_id {
+        1 => "solana",
+        2 => "ethereum",
+        3 => "terra",
+        4 => "bsc",
+        5 => "polygon",
+        6 => "avalanche",
+        7 => "oasis",
+        8 => "algorand",
+        9 => "aurora",
+        10 => "fantom",
+        11 => "karura",
+        12 => "acala",
+        13 => "klaytn",
+        14 => "celo",
+        16 => "moonbeam",
+        23 => "arbitrum",
+        24 => "optimism",
+        30 => "base",
+        _ => "unknown",
+    }
+    .to_string()
+}
+
+struct WormholeNftBridgeDecoder;
+
+impl BridgeProtocolDecoder for WormholeNftBridgeDecoder {
+    fn program_id(&self) -> &'static str {
+        WORMHOLE_NFT_BRIDGE_PROGRAM_ID
+    }
+
+    fn protocol_name(&self) -> &'static str {
+        "wormhole_nft"
+    }
+
+    fn decode(
+        &self,
+        instruction_data: &[u8],
+        account_indexes: &[u8],
+        resolved_accounts: &[String],
+    ) -> Option<DecodedBridgeEvent> {
+        let tag = *instruction_data.first()?;
+
+        if tag == WORMHOLE_NFT_IX_TRANSFER_WRAPPED || tag == WORMHOLE_NFT_IX_TRANSFER_NATIVE {
+            // Outbound: the instruction's own args carry the transfer terms
+            // directly -- no VAA exists yet at send time.
+            let args = decode_wormhole_nft_transfer_args(instruction_data)?;
+            return Some(DecodedBridgeEvent {
+                event_type: "nft_send".to_string(),
+                source_chain: "solana".to_string(),
+                destination_chain: wormhole_chain_name(args.target_chain),
+                sender_address: String::new(),
+                receiver_address: bs58::encode(args.target_address).into_string(),
+                // The mint is referenced by account, not carried in the args.
+                token_address: String::new(),
+                token_id: String::new(),
+                amount: "1".to_string(),
+                nonce: args.nonce as u64,
+                message_hash: String::new(),
+            });
+        }
+
+        if tag == WORMHOLE_NFT_IX_COMPLETE_NATIVE || tag == WORMHOLE_NFT_IX_COMPLETE_WRAPPED {
+            // Inbound: the transfer terms live in the posted-VAA account this
+            // instruction redeems, which isn't readable from block data
+            // alone. Report the referenced VAA account, not a fabricated body.
+            let vaa_account = account_indexes
+                .get(WORMHOLE_VAA_ACCOUNT_INDEX)
+                .and_then(|&idx| resolved_accounts.get(idx as usize))
+                .cloned()
+                .unwrap_or_default();
+            return Some(DecodedBridgeEvent {
+                event_type: "nft_receive".to_string(),
+                source_chain: "unknown".to_string(),
+                destination_chain: "solana".to_string(),
+                sender_address: String::new(),
+                receiver_address: String::new(),
+                token_address: String::new(),
+                token_id: String::new(),
+                amount: "1".to_string(),
+                nonce: 0,
+                message_hash: vaa_account,
+            });
+        }
+
+        None
     }
 }
 
-fn determine_bridge_event_type(transaction: &ConfirmedTransaction) -> String {
-    // In a real implementation, we would analyze the transaction logs and instruction data
-    // to determine if this is a send or receive
-    // For this example, we'll default to "send"
-    "send".to_string()
+/// Fixed leading fields of the NFT Bridge's native `TransferNative`/
+/// `TransferWrapped` instruction args: nonce(4) + target_address(32) +
+/// target_chain(2), little-endian, immediately following the 1-byte
+/// instruction tag. Taken from the public NFT Bridge Rust source; not
+/// vendored in this sandbox to check byte-for-byte, so best-effort.
+struct WormholeNftTransferArgs {
+    nonce: u32,
+    target_address: [u8; 32],
+    target_chain: u16,
 }
 
-fn extract_chain_info(transaction: &ConfirmedTransaction) -> (String, String) {
-    // In a real implementation, we would extract the source and destination chains
-    // For this example, we'll use placeholder values
-    ("solana".to_string(), "ethereum".to_string())
+fn decode_wormhole_nft_transfer_args(instruction_data: &[u8]) -> Option<WormholeNftTransferArgs> {
+    if instruction_data.len() < 1 + 4 + 32 + 2 {
+        return None;
+    }
+    let nonce = u32::from_le_bytes(instruction_data[1..5].try_into().ok()?);
+    let target_address: [u8; 32] = instruction_data[5..37].try_into().ok()?;
+    let target_chain = u16::from_le_bytes(instruction_data[37..39].try_into().ok()?);
+    Some(WormholeNftTransferArgs { nonce, target_address, target_chain })
 }
 
-fn extract_sender_receiver_addresses(transaction: &ConfirmedTransaction) -> (String, String) {
-    // In a real implementation, we would extract the sender and receiver addresses
-    // For this example, we'll use placeholder values
-    ("sender_address_placeholder".to_string(), "receiver_address_placeholder".to_string())
+struct LayerZeroDecoder;
+
+impl BridgeProtocolDecoder for LayerZeroDecoder {
+    fn program_id(&self) -> &'static str {
+        LAYERZERO_PROGRAM_ID
+    }
+
+    fn protocol_name(&self) -> &'static str {
+        "layerzero"
+    }
+
+    fn decode(
+        &self,
+        instruction_data: &[u8],
+        _account_indexes: &[u8],
+        _resolved_accounts: &[String],
+    ) -> Option<DecodedBridgeEvent> {
+        // LayerZero V2 endpoint instruction layout: [0] = discriminator (0 = send,
+        // 1 = receive), [1..3] = source EID (u16 BE), [3..11] = nonce (u64 BE),
+        // [11..43] = message GUID.
+        if instruction_data.len() < 43 {
+            return None;
+        }
+
+        let event_type = match instruction_data[0] {
+            0 => "send",
+            1 => "receive",
+            _ => return None,
+        };
+        let source_eid = u16::from_be_bytes(instruction_data[1..3].try_into().ok()?);
+        let nonce = u64::from_be_bytes(instruction_data[3..11].try_into().ok()?);
+        let guid = &instruction_data[11..43];
+
+        Some(DecodedBridgeEvent {
+            event_type: event_type.to_string(),
+            source_chain: layerzero_chain_name(source_eid),
+            destination_chain: "solana".to_string(),
+            sender_address: String::new(),
+            receiver_address: String::new(),
+            token_address: String::new(),
+            token_id: String::new(),
+            amount: "0".to_string(),
+            nonce,
+            message_hash: hex::encode(guid),
+        })
+    }
+}
+
+/// Map a LayerZero endpoint ID to its canonical chain name.
+fn layerzero_chain_name(endpoint_id: u16) -> String {
+    match endpoint_id {
+        101 => "ethereum",
+        102 => "bsc",
+        106 => "avalanche",
+        109 => "polygon",
+        110 => "arbitrum",
+        111 => "optimism",
+        184 => "base",
+        _ => "unknown",
+    }
+    .to_string()
 }
 
-fn extract_token_info(transaction: &ConfirmedTransaction) -> (String, String, String) {
-    // In a real implementation, we would extract the token address, token ID, and amount
-    // For this example, we'll use placeholder values
-    ("token_address_placeholder".to_string(), "token_id_placeholder".to_string(), "1000000000".to_string())
+/// Output of the bridge event extraction map module.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BridgeEvents {
+    #[prost(message, repeated, tag = "1")]
+    pub events: ::prost::alloc::vec::Vec<BridgeEvent>,
 }
 
-fn extract_bridge_details(transaction: &ConfirmedTransaction) -> (String, u64, String) {
-    // In a real implementation, we would extract the fee, nonce, and message hash
-    // For this example, we'll use placeholder values
-    ("10000000".to_string(), 12345, "message_hash_placeholder".to_string())
+/// A single cross-chain bridge transfer observed on Solana.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BridgeEvent {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub transaction_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "3")]
+    pub block_number: u64,
+    #[prost(string, tag = "4")]
+    pub block_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "5")]
+    pub timestamp: u64,
+    #[prost(string, tag = "6")]
+    pub bridge_protocol: ::prost::alloc::string::String,
+    #[prost(string, tag = "7")]
+    pub event_type: ::prost::alloc::string::String,
+    #[prost(string, tag = "8")]
+    pub source_chain: ::prost::alloc::string::String,
+    #[prost(string, tag = "9")]
+    pub destination_chain: ::prost::alloc::string::String,
+    #[prost(string, tag = "10")]
+    pub sender_address: ::prost::alloc::string::String,
+    #[prost(string, tag = "11")]
+    pub receiver_address: ::prost::alloc::string::String,
+    #[prost(string, tag = "12")]
+    pub token_address: ::prost::alloc::string::String,
+    #[prost(string, tag = "13")]
+    pub token_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "14")]
+    pub amount: ::prost::alloc::string::String,
+    #[prost(string, tag = "15")]
+    pub fee: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "16")]
+    pub nonce: u64,
+    #[prost(string, tag = "17")]
+    pub message_hash: ::prost::alloc::string::String,
+    #[prost(uint64, optional, tag = "18")]
+    pub cu_requested: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "19")]
+    pub cu_consumed: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "20")]
+    pub priority_fee_micro_lamports: ::core::option::Option<u64>,
+    #[prost(uint64, tag = "21")]
+    pub total_fee: u64,
 }
 
-// This module is auto-generated from the protobuf definitions
-mod pb {
-    pub mod bridge {
-        pub mod v1 {
-            include!(concat!(env!("OUT_DIR"), "/bridge.v1.rs"));
+/// Output of the per-account fee-pressure extraction map module.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AccountFeeStatsEvents {
+    #[prost(message, repeated, tag = "1")]
+    pub stats: ::prost::alloc::vec::Vec<AccountFeeStat>,
+}
+
+/// Priority-fee percentile summary for a single writable account, aggregated
+/// across a block's bridge/marketplace transactions that touch it.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AccountFeeStat {
+    #[prost(string, tag = "1")]
+    pub account: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub block_number: u64,
+    #[prost(uint64, tag = "3")]
+    pub min_priority_fee: u64,
+    #[prost(uint64, tag = "4")]
+    pub median_priority_fee: u64,
+    #[prost(uint64, tag = "5")]
+    pub p75_priority_fee: u64,
+    #[prost(uint64, tag = "6")]
+    pub p90_priority_fee: u64,
+    #[prost(uint64, tag = "7")]
+    pub p95_priority_fee: u64,
+    #[prost(uint64, tag = "8")]
+    pub max_priority_fee: u64,
+    #[prost(uint64, tag = "9")]
+    pub sample_count: u64,
+}
+
+/// Extract per-writable-account priority-fee pressure for every bridge or
+/// marketplace program invocation in a block, so callers can spot hot accounts
+/// driving up prioritization fees.
+pub fn extract_account_fee_stats(block: Block, alt_store: StoreGetProto<AltTable>) -> Result<AccountFeeStatsEvents, Error> {
+    let block_number = block.slot;
+    let alt_cache = build_alt_cache(&block);
+    let mut fees_by_account: HashMap<String, Vec<u64>> = HashMap::new();
+
+    for transaction in &block.transactions {
+        if !transaction
+            .meta
+            .as_ref()
+            .map(|m| m.status.clone().unwrap_or_default().err.is_none())
+            .unwrap_or(false)
+        {
+            continue;
+        }
+        let Some(message) = transaction
+            .transaction
+            .as_ref()
+            .and_then(|t| t.message.as_ref())
+        else {
+            continue;
+        };
+
+        let resolved_accounts = resolve_account_keys(transaction, &alt_cache, &alt_store);
+        let writable = writable_account_mask(transaction, resolved_accounts.len(), &alt_cache, &alt_store);
+        let fee_info = extract_compute_fee_info(transaction, &resolved_accounts);
+        let Some(priority_fee) = fee_info.priority_fee_micro_lamports else {
+            continue;
+        };
+
+        // Each writable account contributes one sample per transaction, however
+        // many bridge/marketplace instructions in it reference that account.
+        let mut touched: std::collections::HashSet<&String> = std::collections::HashSet::new();
+        for instruction in &message.instructions {
+            let Some(program_key) = resolved_accounts.get(instruction.program_id_index as usize) else {
+                continue;
+            };
+            let is_bridge_or_marketplace = program_key == WORMHOLE_PROGRAM_ID
+                || program_key == WORMHOLE_NFT_BRIDGE_PROGRAM_ID
+                || program_key == LAYERZERO_PROGRAM_ID
+                || program_key == MAGIC_EDEN_PROGRAM_ID
+                || program_key == TENSOR_PROGRAM_ID;
+            if !is_bridge_or_marketplace {
+                continue;
+            }
+
+            for &account_index in &instruction.accounts {
+                if !writable.get(account_index as usize).copied().unwrap_or(false) {
+                    continue;
+                }
+                if let Some(account) = resolved_accounts.get(account_index as usize) {
+                    if touched.insert(account) {
+                        fees_by_account.entry(account.clone()).or_default().push(priority_fee);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut stats = Vec::new();
+    for (account, fees) in fees_by_account {
+        let sample_count = fees.len() as u64;
+        if let Some(percentiles) = compute_fee_percentiles(&fees) {
+            stats.push(AccountFeeStat {
+                account,
+                block_number,
+                min_priority_fee: percentiles.min,
+                median_priority_fee: percentiles.median,
+                p75_priority_fee: percentiles.p75,
+                p90_priority_fee: percentiles.p90,
+                p95_priority_fee: percentiles.p95,
+                max_priority_fee: percentiles.max,
+                sample_count,
+            });
         }
     }
+
+    Ok(AccountFeeStatsEvents { stats })
 }
@@ -1,23 +1,45 @@
 mod nft;
 mod marketplace;
 mod bridge;
+mod priority_fee;
+mod nft_bridge;
+mod utils;
 
 use substreams::errors::Error;
+use substreams::store::StoreGetProto;
 use substreams_solana::pb::sf::solana::r#type::v1::Block;
 
+/// Extract the lookup tables created/extended in this block, for persisting
+/// cross-block into `store_alt_tables`.
 #[substreams::handlers::map]
-pub fn map_nft_events(block: Block) -> Result<nft::NFTEvents, Error> {
-    nft::extract_nft_events(block)
+pub fn map_alt_table_updates(block: Block) -> Result<utils::AltTableUpdates, Error> {
+    Ok(utils::extract_alt_table_updates(&block))
+}
+
+/// Cross-block snapshot of every address lookup table this pipeline has ever
+/// observed being created/extended, so versioned transactions whose lookup
+/// tables predate the current block still resolve correctly.
+#[substreams::handlers::store]
+pub fn store_alt_tables(updates: utils::AltTableUpdates) -> Result<(), Error> {
+    for table in updates.tables {
+        substreams::store::set(format!("alt_table:{}", table.table_key), &table);
+    }
+    Ok(())
 }
 
 #[substreams::handlers::map]
-pub fn map_marketplace_events(block: Block) -> Result<marketplace::MarketplaceEvents, Error> {
-    marketplace::extract_marketplace_events(block)
+pub fn map_nft_events(block: Block, alt_store: StoreGetProto<utils::AltTable>) -> Result<nft::NFTEvents, Error> {
+    nft::extract_nft_events(block, alt_store)
 }
 
 #[substreams::handlers::map]
-pub fn map_bridge_events(block: Block) -> Result<bridge::BridgeEvents, Error> {
-    bridge::extract_bridge_events(block)
+pub fn map_marketplace_events(block: Block, alt_store: StoreGetProto<utils::AltTable>) -> Result<marketplace::MarketplaceEvents, Error> {
+    marketplace::extract_marketplace_events(block, alt_store)
+}
+
+#[substreams::handlers::map]
+pub fn map_bridge_events(block: Block, alt_store: StoreGetProto<utils::AltTable>) -> Result<bridge::BridgeEvents, Error> {
+    bridge::extract_bridge_events(block, alt_store)
 }
 
 #[substreams::handlers::store]
@@ -40,6 +62,56 @@ pub fn store_marketplace_events(events: marketplace::MarketplaceEvents) -> Resul
 pub fn store_bridge_events(events: bridge::BridgeEvents) -> Result<(), Error> {
     for event in events.events {
         substreams::store::set(format!("bridge:{}", event.id), &event);
+
+        // NFT bridge transfers that carry a token ID are also indexed by it, so
+        // a bridged NFT's cross-chain history can be looked up by joining
+        // against `map_nft_events` on that same ID. `bridge.rs`'s Wormhole
+        // NFT-bridge decoder leaves `token_id` empty, so its events are not
+        // written under this key.
+        let is_nft_bridge_event = event.event_type == "nft_send" || event.event_type == "nft_receive";
+        if is_nft_bridge_event && !event.token_id.is_empty() {
+            substreams::store::set(format!("bridge_nft:{}", event.token_id), &event);
+        }
+    }
+    Ok(())
+}
+
+#[substreams::handlers::map]
+pub fn map_account_fee_stats(block: Block, alt_store: StoreGetProto<utils::AltTable>) -> Result<bridge::AccountFeeStatsEvents, Error> {
+    bridge::extract_account_fee_stats(block, alt_store)
+}
+
+#[substreams::handlers::map]
+pub fn map_priority_fee_stats(block: Block, alt_store: StoreGetProto<utils::AltTable>) -> Result<priority_fee::PriorityFeeStats, Error> {
+    priority_fee::extract_priority_fee_stats(block, alt_store)
+}
+
+#[substreams::handlers::store]
+pub fn store_priority_fee_stats(stats: priority_fee::PriorityFeeStats) -> Result<(), Error> {
+    substreams::store::set(format!("priority_fee:{}", stats.block_number), &stats);
+    Ok(())
+}
+
+#[substreams::handlers::store]
+pub fn store_account_fee_stats(stats: bridge::AccountFeeStatsEvents) -> Result<(), Error> {
+    for stat in stats.stats {
+        substreams::store::set(format!("account_fee_stats:{}", stat.account), &stat);
+    }
+    Ok(())
+}
+
+#[substreams::handlers::map]
+pub fn map_nft_bridge_events(block: Block, alt_store: StoreGetProto<utils::AltTable>) -> Result<nft_bridge::NFTBridgeEvents, Error> {
+    nft_bridge::extract_nft_bridge_events(block, alt_store)
+}
+
+#[substreams::handlers::store]
+pub fn store_nft_bridge_events(events: nft_bridge::NFTBridgeEvents) -> Result<(), Error> {
+    for event in events.out_events {
+        substreams::store::set(format!("nft_bridge_out:{}", event.id), &event);
+    }
+    for event in events.in_events {
+        substreams::store::set(format!("nft_bridge_in:{}", event.id), &event);
     }
     Ok(())
 }
@@ -1,24 +1,28 @@
 use substreams::errors::Error;
+use substreams::store::StoreGetProto;
 use substreams_solana::pb::sf::solana::r#type::v1::Block;
 use substreams_solana::pb::sf::solana::r#type::v1::ConfirmedTransaction;
 
-// Re-export the protobuf types
-pub use crate::pb::marketplace::v1::*;
+use crate::utils::{build_alt_cache, extract_compute_fee_info, resolve_account_keys, AltCache, AltTable};
 
 // Known marketplace program IDs on Solana
-const MAGIC_EDEN_PROGRAM_ID: &str = "M2mx93ekt1fmXSVkTrUL9xVFHkmME8HTUi5Cyc5aF7K";
-const TENSOR_PROGRAM_ID: &str = "TSWAPaqyCSx2KABk68Shruf4rp7CxcNi8hAsbdwmHbN";
+pub(crate) const MAGIC_EDEN_PROGRAM_ID: &str = "M2mx93ekt1fmXSVkTrUL9xVFHkmME8HTUi5Cyc5aF7K";
+pub(crate) const TENSOR_PROGRAM_ID: &str = "TSWAPaqyCSx2KABk68Shruf4rp7CxcNi8hAsbdwmHbN";
 
-pub fn extract_marketplace_events(block: Block) -> Result<MarketplaceEvents, Error> {
+/// Wrapped SOL mint, used as the default `currency_address` for native-SOL
+/// marketplace activity.
+const WRAPPED_SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+pub fn extract_marketplace_events(block: Block, alt_store: StoreGetProto<AltTable>) -> Result<MarketplaceEvents, Error> {
     let mut events = MarketplaceEvents { events: vec![] };
     let block_number = block.slot;
     let block_hash = block.blockhash.clone();
     let timestamp = block.block_time.as_ref().map(|t| t.timestamp).unwrap_or(0) as u64;
+    let alt_cache = build_alt_cache(&block);
+    let decoders = marketplace_decoders();
 
     for transaction in block.transactions {
-        if let Some(marketplace_event) = process_transaction(transaction, block_number, &block_hash, timestamp) {
-            events.events.push(marketplace_event);
-        }
+        events.events.extend(process_transaction(transaction, block_number, &block_hash, timestamp, &alt_cache, &alt_store, &decoders));
     }
 
     Ok(events)
@@ -29,105 +33,367 @@ fn process_transaction(
     block_number: u64,
     block_hash: &str,
     timestamp: u64,
-) -> Option<MarketplaceEvent> {
+    alt_cache: &AltCache,
+    alt_store: &StoreGetProto<AltTable>,
+    decoders: &[Box<dyn MarketplaceProtocolDecoder>],
+) -> Vec<MarketplaceEvent> {
+    let mut events = Vec::new();
+
     // Skip failed transactions
-    if !transaction.meta.as_ref()?.status.unwrap_or_default().err.is_none() {
-        return None;
+    let Some(meta) = transaction.meta.as_ref() else {
+        return events;
+    };
+    if !meta.status.clone().unwrap_or_default().err.is_none() {
+        return events;
     }
+    let Some(transaction_hash) = transaction
+        .transaction
+        .as_ref()
+        .map(|t| bs58::encode(&t.signatures[0]).into_string())
+    else {
+        return events;
+    };
 
-    let transaction_hash = bs58::encode(&transaction.transaction.as_ref()?.signatures[0]).into_string();
-    
-    // Look for marketplace program invocations
-    for account_key in &transaction.transaction.as_ref()?.message.as_ref()?.account_keys {
-        if account_key == MAGIC_EDEN_PROGRAM_ID || account_key == TENSOR_PROGRAM_ID {
-            // This is a transaction involving a marketplace
-            // In a real implementation, we would parse the instruction data and logs
-            // to determine the exact event type and extract relevant information
-            
-            // For this example, we'll create a simplified marketplace event
-            let marketplace = determine_marketplace(account_key);
-            let event_type = determine_marketplace_event_type(&transaction);
-            let (token_address, token_id) = extract_token_info(&transaction);
-            let collection_address = extract_collection_address(&transaction);
-            let (seller_address, buyer_address) = extract_seller_buyer_addresses(&transaction);
-            let (currency_address, price) = extract_price_info(&transaction);
-            let (marketplace_fee, creator_fee) = extract_fee_info(&transaction);
-            
-            return Some(MarketplaceEvent {
-                id: format!("{}-{}", transaction_hash, 0),
-                transaction_hash,
-                block_number,
-                block_hash: block_hash.to_string(),
-                timestamp,
-                marketplace,
-                event_type,
-                token_address,
-                token_id,
-                collection_address,
-                seller_address,
-                buyer_address,
-                currency_address,
-                price,
-                quantity: 1,
-                marketplace_fee,
-                creator_fee,
-            });
+    // Resolve the full account list, including any accounts pulled in through
+    // address lookup tables on v0 (versioned) transactions.
+    let resolved_accounts = resolve_account_keys(&transaction, alt_cache, alt_store);
+    let Some(message) = transaction.transaction.as_ref().and_then(|t| t.message.as_ref()) else {
+        return events;
+    };
+    let fee_info = extract_compute_fee_info(&transaction, &resolved_accounts);
+
+    // Matches each instruction against a registered `MarketplaceProtocolDecoder`;
+    // new marketplace programs are supported by adding an impl and listing it in
+    // `marketplace_decoders()`, not by editing this loop.
+    // Walk the top-level compiled instructions, tagged by their own index,
+    // followed by any inner (CPI) instructions, tagged `"{outer}.{inner}"` --
+    // so CPI-wrapped marketplace calls (e.g. through an aggregator) aren't
+    // missed.
+    let mut all_instructions: Vec<(String, u32, &[u8])> = Vec::new();
+    for (index, instruction) in message.instructions.iter().enumerate() {
+        all_instructions.push((index.to_string(), instruction.program_id_index, instruction.data.as_slice()));
+    }
+    for inner in &meta.inner_instructions {
+        for (inner_index, instruction) in inner.instructions.iter().enumerate() {
+            all_instructions.push((
+                format!("{}.{}", inner.index, inner_index),
+                instruction.program_id_index,
+                instruction.data.as_slice(),
+            ));
         }
     }
-    
-    None
-}
 
-fn determine_marketplace(program_id: &str) -> String {
-    match program_id {
-        MAGIC_EDEN_PROGRAM_ID => "magic_eden".to_string(),
-        TENSOR_PROGRAM_ID => "tensor".to_string(),
-        _ => "unknown".to_string(),
+    // Every NFT that actually changed hands in this transaction gets its own
+    // entry here, in the order its balance delta appears; consuming one per
+    // matched `sale` instruction (rather than resolving once and reusing it
+    // for every instruction) keeps a multi-sale transaction -- e.g. an
+    // aggregator routing through more than one marketplace instruction --
+    // from smearing the first sale's parties across all of them.
+    let transfers = resolve_transfer_parties(&transaction, &resolved_accounts);
+    let mut transfer_cursor = 0;
+
+    for (label, program_id_index, data) in all_instructions {
+        let Some(program_key) = resolved_accounts.get(program_id_index as usize) else {
+            continue;
+        };
+        let Some(decoder) = decoders.iter().find(|d| d.program_id() == program_key) else {
+            continue;
+        };
+        let Some(decoded) = decoder.decode(data) else {
+            continue;
+        };
+
+        // Listings/offers/cancels don't move the NFT, so there's no balance
+        // delta to cross-reference -- only a completed sale consumes one of
+        // this transaction's resolved transfers.
+        let (seller_address, buyer_address, token_address, token_id, resolved_price) =
+            if decoded.event_type == "sale" {
+                let transfer = transfers.get(transfer_cursor).cloned().unwrap_or_default();
+                transfer_cursor += 1;
+                transfer
+            } else {
+                Default::default()
+            };
+
+        let price = resolved_price
+            .map(|p| p.to_string())
+            .unwrap_or_else(|| decoded.price.map(|p| p.to_string()).unwrap_or_else(|| "0".to_string()));
+        let price_lamports: u64 = price.parse().unwrap_or(0);
+        let (marketplace_fee, creator_fee) = estimate_fees(&decoded.event_type, price_lamports);
+
+        events.push(MarketplaceEvent {
+            id: format!("{}-{}", transaction_hash, label),
+            transaction_hash: transaction_hash.clone(),
+            block_number,
+            block_hash: block_hash.to_string(),
+            timestamp,
+            marketplace: decoder.marketplace_name().to_string(),
+            event_type: decoded.event_type,
+            token_address,
+            token_id,
+            // The collection an NFT belongs to is recorded inside its
+            // Metaplex metadata account, whose content isn't observable from
+            // block-level transaction data (no generic account-state reads
+            // here, only structured meta fields), so this is left empty
+            // rather than fabricated.
+            collection_address: String::new(),
+            seller_address,
+            buyer_address,
+            currency_address: WRAPPED_SOL_MINT.to_string(),
+            price,
+            quantity: 1,
+            marketplace_fee,
+            creator_fee,
+            cu_requested: fee_info.cu_requested,
+            cu_consumed: fee_info.cu_consumed,
+            priority_fee_micro_lamports: fee_info.priority_fee_micro_lamports,
+            total_fee: fee_info.total_fee,
+        });
     }
-}
 
-fn determine_marketplace_event_type(transaction: &ConfirmedTransaction) -> String {
-    // In a real implementation, we would analyze the transaction logs and instruction data
-    // to determine if this is a listing, sale, offer, or cancel
-    // For this example, we'll default to "sale"
-    "sale".to_string()
+    events
 }
 
-fn extract_token_info(transaction: &ConfirmedTransaction) -> (String, String) {
-    // In a real implementation, we would extract the token address and ID
-    // For this example, we'll use placeholder values
-    ("token_address_placeholder".to_string(), "token_id_placeholder".to_string())
+/// Recover, for every NFT that changed hands in this transaction, the mint,
+/// seller, buyer, and sale proceeds, by cross-referencing the transaction's
+/// pre/post token-balance and SOL-balance snapshots: a seller is an owner
+/// whose token balance for some mint drops from 1 to 0, a buyer is an owner
+/// whose balance for that same mint rises from 0 to 1, and the price is the
+/// resulting SOL gain in the seller's own wallet (when that wallet also
+/// appears as a top-level account). Entries are returned in the order their
+/// seller-side balance delta appears, so callers matching more than one
+/// marketplace instruction in the same transaction can consume them in order
+/// instead of reusing a single resolution for every instruction.
+fn resolve_transfer_parties(
+    transaction: &ConfirmedTransaction,
+    resolved_accounts: &[String],
+) -> Vec<(String, String, String, String, Option<u64>)> {
+    let Some(meta) = transaction.meta.as_ref() else {
+        return Vec::new();
+    };
+
+    // Raw token amount carried by a balance-snapshot entry, if any.
+    let amount_of = |balance: &_| -> Option<u64> { balance.ui_token_amount.as_ref()?.amount.parse().ok() };
+
+    let mut sellers: Vec<(String, String)> = Vec::new(); // (mint, seller)
+    let mut buyers: Vec<(String, String)> = Vec::new(); // (mint, buyer)
+
+    for post in &meta.post_token_balances {
+        let Some(pre) = meta
+            .pre_token_balances
+            .iter()
+            .find(|pre| pre.account_index == post.account_index)
+        else {
+            continue;
+        };
+        if amount_of(pre) == Some(1) && amount_of(post) == Some(0) {
+            sellers.push((pre.mint.clone(), pre.owner.clone()));
+        }
+        if amount_of(pre).unwrap_or(0) == 0 && amount_of(post) == Some(1) {
+            buyers.push((post.mint.clone(), post.owner.clone()));
+        }
+    }
+
+    sellers
+        .into_iter()
+        .map(|(mint, seller)| {
+            let buyer = buyers
+                .iter()
+                .position(|(buyer_mint, _)| *buyer_mint == mint)
+                .map(|index| buyers.remove(index).1)
+                .unwrap_or_default();
+
+            let price = resolved_accounts.iter().position(|a| *a == seller).and_then(|index| {
+                let pre_lamports = *meta.pre_balances.get(index)?;
+                let post_lamports = *meta.post_balances.get(index)?;
+                post_lamports.checked_sub(pre_lamports)
+            });
+
+            // Solana's Metaplex NFTs have no separate numeric token ID --
+            // the mint is itself the NFT's unique identifier -- so token_id
+            // mirrors token_address.
+            (seller, buyer, mint.clone(), mint, price)
+        })
+        .collect()
 }
 
-fn extract_collection_address(transaction: &ConfirmedTransaction) -> String {
-    // In a real implementation, we would extract the collection address
-    // For this example, we'll use a placeholder value
-    "collection_address_placeholder".to_string()
+/// Estimate marketplace and creator fees as a share of the sale price. There's
+/// no on-chain fee-config account parsed here, so this mirrors typical
+/// Magic Eden / Tensor defaults: a 2% marketplace fee and a 5% creator
+/// royalty, both zero outside of a `sale`.
+fn estimate_fees(event_type: &str, price_lamports: u64) -> (String, String) {
+    if event_type != "sale" || price_lamports == 0 {
+        return ("0".to_string(), "0".to_string());
+    }
+    let marketplace_fee = price_lamports / 50; // 2%
+    let creator_fee = price_lamports / 20; // 5%
+    (marketplace_fee.to_string(), creator_fee.to_string())
 }
 
-fn extract_seller_buyer_addresses(transaction: &ConfirmedTransaction) -> (String, String) {
-    // In a real implementation, we would extract the seller and buyer addresses
-    // For this example, we'll use placeholder values
-    ("seller_address_placeholder".to_string(), "buyer_address_placeholder".to_string())
+/// Decoded fields recovered from a marketplace program's raw instruction data.
+#[derive(Debug, Clone, Default)]
+struct DecodedMarketplaceEvent {
+    event_type: String,
+    /// Listing/offer price read directly from the instruction's own
+    /// arguments, used when there's no balance-delta to cross-reference
+    /// (e.g. a `listing` or `offer` has not moved the NFT yet).
+    price: Option<u64>,
 }
 
-fn extract_price_info(transaction: &ConfirmedTransaction) -> (String, String) {
-    // In a real implementation, we would extract the currency address and price
-    // For this example, we'll use placeholder values
-    ("So11111111111111111111111111111111111111112".to_string(), "1000000000".to_string())
+/// Decodes a single marketplace program's instruction data into marketplace
+/// event fields. Registering a new marketplace means adding an impl and
+/// listing it in `marketplace_decoders()` -- `process_transaction` never
+/// needs to change.
+trait MarketplaceProtocolDecoder {
+    /// The program ID this decoder recognizes.
+    fn program_id(&self) -> &'static str;
+
+    /// Human-readable marketplace name stored on the emitted `MarketplaceEvent`.
+    fn marketplace_name(&self) -> &'static str;
+
+    /// Decode one instruction's data, returning `None` if it isn't a
+    /// marketplace instruction this decoder understands.
+    fn decode(&self, instruction_data: &[u8]) -> Option<DecodedMarketplaceEvent>;
 }
 
-fn extract_fee_info(transaction: &ConfirmedTransaction) -> (String, String) {
-    // In a real implementation, we would extract the marketplace and creator fees
-    // For this example, we'll use placeholder values
-    ("20000000".to_string(), "50000000".to_string())
+fn marketplace_decoders() -> Vec<Box<dyn MarketplaceProtocolDecoder>> {
+    vec![Box::new(MagicEdenDecoder), Box::new(TensorDecoder)]
 }
 
-// This module is auto-generated from the protobuf definitions
-mod pb {
-    pub mod marketplace {
-        pub mod v1 {
-            include!(concat!(env!("OUT_DIR"), "/marketplace.v1.rs"));
+/// Anchor instruction discriminators for Magic Eden's Auction-House-derived
+/// program: the first 8 bytes of `sha256("global:<instruction_name>")`.
+const MAGIC_EDEN_SELL: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
+const MAGIC_EDEN_BUY: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+const MAGIC_EDEN_EXECUTE_SALE: [u8; 8] = [37, 74, 217, 157, 79, 49, 35, 6];
+const MAGIC_EDEN_CANCEL: [u8; 8] = [232, 219, 223, 41, 219, 236, 220, 190];
+
+struct MagicEdenDecoder;
+
+impl MarketplaceProtocolDecoder for MagicEdenDecoder {
+    fn program_id(&self) -> &'static str {
+        MAGIC_EDEN_PROGRAM_ID
+    }
+
+    fn marketplace_name(&self) -> &'static str {
+        "magic_eden"
+    }
+
+    fn decode(&self, instruction_data: &[u8]) -> Option<DecodedMarketplaceEvent> {
+        if instruction_data.len() < 8 {
+            return None;
         }
+        let discriminator: [u8; 8] = instruction_data[0..8].try_into().ok()?;
+        let args = &instruction_data[8..];
+
+        let (event_type, price) = match discriminator {
+            MAGIC_EDEN_SELL => ("listing", read_u64_arg(args, 0)),
+            MAGIC_EDEN_BUY => ("offer", read_u64_arg(args, 0)),
+            MAGIC_EDEN_EXECUTE_SALE => ("sale", read_u64_arg(args, 0)),
+            MAGIC_EDEN_CANCEL => ("cancel", None),
+            _ => return None,
+        };
+
+        Some(DecodedMarketplaceEvent {
+            event_type: event_type.to_string(),
+            price,
+        })
+    }
+}
+
+/// Legacy (pre-Anchor) single-byte instruction tags used by Tensor's earlier
+/// swap program revisions.
+const TENSOR_LIST: u8 = 0;
+const TENSOR_DELIST: u8 = 1;
+const TENSOR_BUY: u8 = 2;
+const TENSOR_BID: u8 = 3;
+
+struct TensorDecoder;
+
+impl MarketplaceProtocolDecoder for TensorDecoder {
+    fn program_id(&self) -> &'static str {
+        TENSOR_PROGRAM_ID
     }
+
+    fn marketplace_name(&self) -> &'static str {
+        "tensor"
+    }
+
+    fn decode(&self, instruction_data: &[u8]) -> Option<DecodedMarketplaceEvent> {
+        let tag = *instruction_data.first()?;
+        let args = &instruction_data[1..];
+
+        let (event_type, price) = match tag {
+            TENSOR_LIST => ("listing", read_u64_arg(args, 0)),
+            TENSOR_DELIST => ("cancel", None),
+            TENSOR_BUY => ("sale", read_u64_arg(args, 0)),
+            TENSOR_BID => ("offer", read_u64_arg(args, 0)),
+            _ => return None,
+        };
+
+        Some(DecodedMarketplaceEvent {
+            event_type: event_type.to_string(),
+            price,
+        })
+    }
+}
+
+/// Read a little-endian `u64` instruction argument at `offset` into `args`.
+fn read_u64_arg(args: &[u8], offset: usize) -> Option<u64> {
+    args.get(offset..offset + 8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u64::from_le_bytes)
+}
+
+/// Output of the marketplace event extraction map module.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MarketplaceEvents {
+    #[prost(message, repeated, tag = "1")]
+    pub events: ::prost::alloc::vec::Vec<MarketplaceEvent>,
+}
+
+/// A single NFT marketplace activity observed on Solana.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MarketplaceEvent {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub transaction_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "3")]
+    pub block_number: u64,
+    #[prost(string, tag = "4")]
+    pub block_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "5")]
+    pub timestamp: u64,
+    #[prost(string, tag = "6")]
+    pub marketplace: ::prost::alloc::string::String,
+    #[prost(string, tag = "7")]
+    pub event_type: ::prost::alloc::string::String,
+    #[prost(string, tag = "8")]
+    pub token_address: ::prost::alloc::string::String,
+    #[prost(string, tag = "9")]
+    pub token_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "10")]
+    pub collection_address: ::prost::alloc::string::String,
+    #[prost(string, tag = "11")]
+    pub seller_address: ::prost::alloc::string::String,
+    #[prost(string, tag = "12")]
+    pub buyer_address: ::prost::alloc::string::String,
+    #[prost(string, tag = "13")]
+    pub currency_address: ::prost::alloc::string::String,
+    #[prost(string, tag = "14")]
+    pub price: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "15")]
+    pub quantity: u64,
+    #[prost(string, tag = "16")]
+    pub marketplace_fee: ::prost::alloc::string::String,
+    #[prost(string, tag = "17")]
+    pub creator_fee: ::prost::alloc::string::String,
+    #[prost(uint64, optional, tag = "18")]
+    pub cu_requested: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "19")]
+    pub cu_consumed: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "20")]
+    pub priority_fee_micro_lamports: ::core::option::Option<u64>,
+    #[prost(uint64, tag = "21")]
+    pub total_fee: u64,
 }
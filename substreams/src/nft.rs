@@ -1,22 +1,23 @@
 use substreams::errors::Error;
+use substreams::store::StoreGetProto;
 use substreams_solana::pb::sf::solana::r#type::v1::Block;
 use substreams_solana::pb::sf::solana::r#type::v1::ConfirmedTransaction;
 
-// Re-export the protobuf types
-pub use crate::pb::nft::v1::*;
+use crate::utils::{build_alt_cache, extract_compute_fee_info, resolve_account_keys, AltCache, AltTable};
 
 // Known NFT program IDs on Solana
 const METAPLEX_PROGRAM_ID: &str = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s";
 const CANDY_MACHINE_PROGRAM_ID: &str = "cndy3Z4yapfJBmL3ShUp5exZKqR3z33thTzeNMm2gRZ";
 
-pub fn extract_nft_events(block: Block) -> Result<NFTEvents, Error> {
+pub fn extract_nft_events(block: Block, alt_store: StoreGetProto<AltTable>) -> Result<NFTEvents, Error> {
     let mut events = NFTEvents { events: vec![] };
     let block_number = block.slot;
     let block_hash = block.blockhash.clone();
     let timestamp = block.block_time.as_ref().map(|t| t.timestamp).unwrap_or(0) as u64;
+    let alt_cache = build_alt_cache(&block);
 
     for transaction in block.transactions {
-        if let Some(nft_event) = process_transaction(transaction, block_number, &block_hash, timestamp) {
+        if let Some(nft_event) = process_transaction(transaction, block_number, &block_hash, timestamp, &alt_cache, &alt_store) {
             events.events.push(nft_event);
         }
     }
@@ -29,6 +30,8 @@ fn process_transaction(
     block_number: u64,
     block_hash: &str,
     timestamp: u64,
+    alt_cache: &AltCache,
+    alt_store: &StoreGetProto<AltTable>,
 ) -> Option<NFTEvent> {
     // Skip failed transactions
     if !transaction.meta.as_ref()?.status.unwrap_or_default().err.is_none() {
@@ -36,21 +39,26 @@ fn process_transaction(
     }
 
     let transaction_hash = bs58::encode(&transaction.transaction.as_ref()?.signatures[0]).into_string();
-    
+
+    // Resolve the full account list, including any accounts pulled in through
+    // address lookup tables on v0 (versioned) transactions.
+    let resolved_accounts = resolve_account_keys(&transaction, alt_cache, alt_store);
+
     // Look for Metaplex program invocations
-    for account_key in &transaction.transaction.as_ref()?.message.as_ref()?.account_keys {
+    for account_key in &resolved_accounts {
         if account_key == METAPLEX_PROGRAM_ID || account_key == CANDY_MACHINE_PROGRAM_ID {
             // This is a transaction involving NFTs
             // In a real implementation, we would parse the instruction data and logs
             // to determine the exact event type and extract relevant information
-            
+
             // For this example, we'll create a simplified NFT event
             let event_type = determine_nft_event_type(&transaction);
             let (token_address, token_id) = extract_token_info(&transaction);
             let (from_address, to_address) = extract_transfer_addresses(&transaction);
             let collection_address = extract_collection_address(&transaction);
             let metadata = extract_metadata(&transaction);
-            
+            let fee_info = extract_compute_fee_info(&transaction, &resolved_accounts);
+
             return Some(NFTEvent {
                 id: format!("{}-{}", transaction_hash, 0),
                 transaction_hash,
@@ -64,10 +72,14 @@ fn process_transaction(
                 from_address,
                 to_address,
                 metadata: Some(metadata),
+                cu_requested: fee_info.cu_requested,
+                cu_consumed: fee_info.cu_consumed,
+                priority_fee_micro_lamports: fee_info.priority_fee_micro_lamports,
+                total_fee: fee_info.total_fee,
             });
         }
     }
-    
+
     None
 }
 
@@ -119,11 +131,72 @@ fn extract_metadata(transaction: &ConfirmedTransaction) -> NFTMetadata {
     }
 }
 
-// This module is auto-generated from the protobuf definitions
-mod pb {
-    pub mod nft {
-        pub mod v1 {
-            include!(concat!(env!("OUT_DIR"), "/nft.v1.rs"));
-        }
-    }
+/// Output of the NFT event extraction map module.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NFTEvents {
+    #[prost(message, repeated, tag = "1")]
+    pub events: ::prost::alloc::vec::Vec<NFTEvent>,
+}
+
+/// A single NFT mint/transfer/burn event observed on Solana.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NFTEvent {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub transaction_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "3")]
+    pub block_number: u64,
+    #[prost(string, tag = "4")]
+    pub block_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "5")]
+    pub timestamp: u64,
+    #[prost(string, tag = "6")]
+    pub event_type: ::prost::alloc::string::String,
+    #[prost(string, tag = "7")]
+    pub token_address: ::prost::alloc::string::String,
+    #[prost(string, tag = "8")]
+    pub token_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "9")]
+    pub collection_address: ::prost::alloc::string::String,
+    #[prost(string, tag = "10")]
+    pub from_address: ::prost::alloc::string::String,
+    #[prost(string, tag = "11")]
+    pub to_address: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "12")]
+    pub metadata: ::core::option::Option<NFTMetadata>,
+    #[prost(uint64, optional, tag = "13")]
+    pub cu_requested: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "14")]
+    pub cu_consumed: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "15")]
+    pub priority_fee_micro_lamports: ::core::option::Option<u64>,
+    #[prost(uint64, tag = "16")]
+    pub total_fee: u64,
+}
+
+/// Metadata for an NFT, mirroring the Metaplex token-metadata account fields.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NFTMetadata {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub symbol: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub uri: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub description: ::prost::alloc::string::String,
+    #[prost(string, tag = "5")]
+    pub image: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag = "6")]
+    pub attributes: ::prost::alloc::vec::Vec<Attribute>,
+}
+
+/// A single trait/value pair on an NFT's metadata.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Attribute {
+    #[prost(string, tag = "1")]
+    pub trait_type: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub value: ::prost::alloc::string::String,
 }
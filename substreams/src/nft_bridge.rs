@@ -0,0 +1,303 @@
+use substreams::errors::Error;
+use substreams::store::StoreGetProto;
+use substreams_solana::pb::sf::solana::r#type::v1::Block;
+use substreams_solana::pb::sf::solana::r#type::v1::ConfirmedTransaction;
+
+use crate::utils::{
+    build_alt_cache, extract_compute_fee_info, extract_metadata_from_json, resolve_account_keys,
+    AltCache, AltTable,
+};
+
+/// Wormhole NFT-bridge program (also decoded, more narrowly, by `bridge.rs`'s
+/// `WormholeNftBridgeDecoder`). This module exists alongside it to carry the
+/// richer NFT-specific fields -- collection, metadata, wrapped-mint address,
+/// lock-vs-burn semantics -- that a generic `BridgeEvent` has no room for.
+const WORMHOLE_NFT_BRIDGE_PROGRAM_ID: &str = "WnFt12ZrnzZrFZkt2xsNsaNWoQribnuQ5B5FrDbwDhD";
+const MEMO_PROGRAM_ID: &str = "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr";
+
+/// Native NFT-bridge instruction tags: 4/5 are outbound transfer variants
+/// (TransferWrapped/TransferNative -- Wrapped means the NFT being sent back is
+/// burned, Native means it's locked), 1/2 are inbound complete (redeem)
+/// variants (CompleteNative unlocks, CompleteWrapped mints a wrapped copy).
+const WORMHOLE_NFT_IX_TRANSFER_WRAPPED: u8 = 4;
+const WORMHOLE_NFT_IX_TRANSFER_NATIVE: u8 = 5;
+const WORMHOLE_NFT_IX_COMPLETE_NATIVE: u8 = 1;
+const WORMHOLE_NFT_IX_COMPLETE_WRAPPED: u8 = 2;
+
+/// Extract cross-chain NFT-bridge events (an NFT leaving or arriving via the
+/// Wormhole NFT bridge) for every transaction in a block.
+pub fn extract_nft_bridge_events(
+    block: Block,
+    alt_store: StoreGetProto<AltTable>,
+) -> Result<NFTBridgeEvents, Error> {
+    let mut events = NFTBridgeEvents { out_events: vec![], in_events: vec![] };
+    let block_number = block.slot;
+    let block_hash = block.blockhash.clone();
+    let timestamp = block.block_time.as_ref().map(|t| t.timestamp).unwrap_or(0) as u64;
+    let alt_cache = build_alt_cache(&block);
+
+    for transaction in &block.transactions {
+        process_transaction(transaction, block_number, &block_hash, timestamp, &alt_cache, &alt_store, &mut events);
+    }
+
+    Ok(events)
+}
+
+fn process_transaction(
+    transaction: &ConfirmedTransaction,
+    block_number: u64,
+    block_hash: &str,
+    timestamp: u64,
+    alt_cache: &AltCache,
+    alt_store: &StoreGetProto<AltTable>,
+    events: &mut NFTBridgeEvents,
+) {
+    // Skip failed transactions
+    let Some(true) = transaction.meta.as_ref().map(|m| m.status.clone().unwrap_or_default().err.is_none()) else {
+        return;
+    };
+    let Some(tx) = transaction.transaction.as_ref() else {
+        return;
+    };
+    let Some(message) = tx.message.as_ref() else {
+        return;
+    };
+
+    let transaction_hash = bs58::encode(&tx.signatures[0]).into_string();
+    let resolved_accounts = resolve_account_keys(transaction, alt_cache, alt_store);
+    let fee_info = extract_compute_fee_info(transaction, &resolved_accounts);
+
+    // A companion Memo instruction, if present, carries the off-chain JSON
+    // metadata (name/symbol/uri/collection/attributes) for the NFT being moved.
+    let (meta_name, meta_symbol, meta_uri, meta_collection, _meta_attributes) =
+        find_memo_metadata(transaction, &resolved_accounts)
+            .map(|json| extract_metadata_from_json(&json))
+            .unwrap_or_default();
+
+    for (index, instruction) in message.instructions.iter().enumerate() {
+        let Some(program_key) = resolved_accounts.get(instruction.program_id_index as usize) else {
+            continue;
+        };
+        if program_key != WORMHOLE_NFT_BRIDGE_PROGRAM_ID {
+            continue;
+        }
+
+        let Some(&tag) = instruction.data.first() else {
+            continue;
+        };
+
+        let collection = meta_collection.clone().unwrap_or_default();
+
+        match tag {
+            WORMHOLE_NFT_IX_TRANSFER_NATIVE | WORMHOLE_NFT_IX_TRANSFER_WRAPPED => {
+                // Outbound: the instruction's own args carry the transfer
+                // terms directly -- no VAA exists yet at send time.
+                let Some(args) = decode_wormhole_nft_transfer_args(&instruction.data) else {
+                    continue;
+                };
+                let recipient = bs58::encode(args.target_address).into_string();
+
+                events.out_events.push(NFTBridgeOutEvent {
+                    id: format!("{}-{}", transaction_hash, index),
+                    transaction_hash: transaction_hash.clone(),
+                    block_number,
+                    block_hash: block_hash.to_string(),
+                    timestamp,
+                    // The mint is referenced by account, not carried in the
+                    // args; resolving it needs the real NFT Bridge IDL's
+                    // per-variant account order, which isn't pinned down here.
+                    token_address: String::new(),
+                    collection,
+                    name: meta_name.clone(),
+                    symbol: meta_symbol.clone(),
+                    token_uri: meta_uri.clone(),
+                    source_chain_id: 1, // solana, per Wormhole's chain ID registry
+                    destination_chain_id: args.target_chain as u32,
+                    bridge_provider: "wormhole".to_string(),
+                    is_burn: tag == WORMHOLE_NFT_IX_TRANSFER_WRAPPED,
+                    sender_address: String::new(),
+                    recipient_address: recipient,
+                    cu_requested: fee_info.cu_requested,
+                    cu_consumed: fee_info.cu_consumed,
+                    priority_fee_micro_lamports: fee_info.priority_fee_micro_lamports,
+                    total_fee: fee_info.total_fee,
+                });
+            }
+            WORMHOLE_NFT_IX_COMPLETE_NATIVE | WORMHOLE_NFT_IX_COMPLETE_WRAPPED => {
+                // Inbound: the transfer terms live in the posted-VAA account
+                // this instruction redeems, which isn't readable from block
+                // data alone, so the fields normally sourced from it
+                // (token_address/wrapped_mint_address/token_id/recipient/
+                // source_chain_id) are left unpopulated here rather than
+                // fabricated.
+                events.in_events.push(NFTBridgeInEvent {
+                    id: format!("{}-{}", transaction_hash, index),
+                    transaction_hash: transaction_hash.clone(),
+                    block_number,
+                    block_hash: block_hash.to_string(),
+                    timestamp,
+                    token_address: String::new(),
+                    wrapped_mint_address: String::new(),
+                    collection,
+                    name: meta_name.clone(),
+                    symbol: meta_symbol.clone(),
+                    token_uri: meta_uri.clone(),
+                    token_id: String::new(),
+                    source_chain_id: 0, // not recoverable without reading the posted VAA
+                    destination_chain_id: 1, // solana
+                    bridge_provider: "wormhole".to_string(),
+                    recipient_address: String::new(),
+                    cu_requested: fee_info.cu_requested,
+                    cu_consumed: fee_info.cu_consumed,
+                    priority_fee_micro_lamports: fee_info.priority_fee_micro_lamports,
+                    total_fee: fee_info.total_fee,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Find a Memo-program instruction in the same transaction and return its
+/// UTF-8 data, on the assumption that bridging clients attach the NFT's
+/// off-chain JSON metadata as a memo alongside the bridge instruction itself.
+fn find_memo_metadata(transaction: &ConfirmedTransaction, resolved_accounts: &[String]) -> Option<String> {
+    let message = transaction.transaction.as_ref()?.message.as_ref()?;
+    for instruction in &message.instructions {
+        let program_key = resolved_accounts.get(instruction.program_id_index as usize)?;
+        if program_key == MEMO_PROGRAM_ID {
+            return String::from_utf8(instruction.data.clone()).ok();
+        }
+    }
+    None
+}
+
+/// Fixed leading fields of the NFT Bridge's native `TransferNative`/
+/// `TransferWrapped` instruction args: nonce(4) + target_address(32) +
+/// target_chain(2), little-endian, immediately following the 1-byte
+/// instruction tag. Taken from the public NFT Bridge Rust source; not
+/// vendored in this sandbox to check byte-for-byte, so best-effort. Mirrors
+/// `bridge.rs`'s `decode_wormhole_nft_transfer_args`.
+struct WormholeNftTransferArgs {
+    nonce: u32,
+    target_address: [u8; 32],
+    target_chain: u16,
+}
+
+fn decode_wormhole_nft_transfer_args(instruction_data: &[u8]) -> Option<WormholeNftTransferArgs> {
+    if instruction_data.len() < 1 + 4 + 32 + 2 {
+        return None;
+    }
+    let nonce = u32::from_le_bytes(instruction_data[1..5].try_into().ok()?);
+    let target_address: [u8; 32] = instruction_data[5..37].try_into().ok()?;
+    let target_chain = u16::from_le_bytes(instruction_data[37..39].try_into().ok()?);
+    Some(WormholeNftTransferArgs { nonce, target_address, target_chain })
+}
+
+/// Output of the NFT-bridge event extraction map module.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NFTBridgeEvents {
+    #[prost(message, repeated, tag = "1")]
+    pub out_events: ::prost::alloc::vec::Vec<NFTBridgeOutEvent>,
+    #[prost(message, repeated, tag = "2")]
+    pub in_events: ::prost::alloc::vec::Vec<NFTBridgeInEvent>,
+}
+
+/// An NFT leaving Solana through the Wormhole NFT bridge, locked in (or
+/// burned from) a vault on this chain pending mint/unlock on the destination.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NFTBridgeOutEvent {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub transaction_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "3")]
+    pub block_number: u64,
+    #[prost(string, tag = "4")]
+    pub block_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "5")]
+    pub timestamp: u64,
+    #[prost(string, tag = "6")]
+    pub token_address: ::prost::alloc::string::String,
+    #[prost(string, tag = "7")]
+    pub collection: ::prost::alloc::string::String,
+    #[prost(string, tag = "8")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag = "9")]
+    pub symbol: ::prost::alloc::string::String,
+    #[prost(string, tag = "10")]
+    pub token_uri: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "11")]
+    pub source_chain_id: u32,
+    #[prost(uint32, tag = "12")]
+    pub destination_chain_id: u32,
+    #[prost(string, tag = "13")]
+    pub bridge_provider: ::prost::alloc::string::String,
+    /// `true` if the NFT was a wrapped token burned on this side (it is being
+    /// sent back, and the destination unlocks the original); `false` if it was
+    /// a native NFT locked in a vault (the destination mints a wrapped copy).
+    #[prost(bool, tag = "14")]
+    pub is_burn: bool,
+    #[prost(string, tag = "15")]
+    pub sender_address: ::prost::alloc::string::String,
+    #[prost(string, tag = "16")]
+    pub recipient_address: ::prost::alloc::string::String,
+    #[prost(uint64, optional, tag = "17")]
+    pub cu_requested: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "18")]
+    pub cu_consumed: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "19")]
+    pub priority_fee_micro_lamports: ::core::option::Option<u64>,
+    #[prost(uint64, tag = "20")]
+    pub total_fee: u64,
+}
+
+/// An NFT arriving on Solana through the Wormhole NFT bridge: either unlocked
+/// from a vault (it originated here) or freshly minted as a wrapped token.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NFTBridgeInEvent {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub transaction_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "3")]
+    pub block_number: u64,
+    #[prost(string, tag = "4")]
+    pub block_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "5")]
+    pub timestamp: u64,
+    #[prost(string, tag = "6")]
+    pub token_address: ::prost::alloc::string::String,
+    /// The wrapped token minted on Solana, when this event mints one (empty
+    /// when an existing, previously-locked native NFT is merely being
+    /// unlocked). `process_transaction` does not recover it and always leaves
+    /// it empty.
+    #[prost(string, tag = "7")]
+    pub wrapped_mint_address: ::prost::alloc::string::String,
+    #[prost(string, tag = "8")]
+    pub collection: ::prost::alloc::string::String,
+    #[prost(string, tag = "9")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag = "10")]
+    pub symbol: ::prost::alloc::string::String,
+    #[prost(string, tag = "11")]
+    pub token_uri: ::prost::alloc::string::String,
+    #[prost(string, tag = "12")]
+    pub token_id: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "13")]
+    pub source_chain_id: u32,
+    #[prost(uint32, tag = "14")]
+    pub destination_chain_id: u32,
+    #[prost(string, tag = "15")]
+    pub bridge_provider: ::prost::alloc::string::String,
+    #[prost(string, tag = "16")]
+    pub recipient_address: ::prost::alloc::string::String,
+    #[prost(uint64, optional, tag = "17")]
+    pub cu_requested: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "18")]
+    pub cu_consumed: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "19")]
+    pub priority_fee_micro_lamports: ::core::option::Option<u64>,
+    #[prost(uint64, tag = "20")]
+    pub total_fee: u64,
+}
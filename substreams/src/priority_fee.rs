@@ -0,0 +1,177 @@
+use substreams::errors::Error;
+use substreams::store::StoreGetProto;
+use substreams_solana::pb::sf::solana::r#type::v1::Block;
+
+use crate::utils::{
+    build_alt_cache, compute_fee_percentiles, extract_compute_fee_info, resolve_account_keys,
+    writable_account_mask, AltTable, FeePercentiles,
+};
+
+pub fn extract_priority_fee_stats(
+    block: Block,
+    alt_store: StoreGetProto<AltTable>,
+) -> Result<PriorityFeeStats, Error> {
+    let block_number = block.slot;
+    let block_hash = block.blockhash.clone();
+    let timestamp = block.block_time.as_ref().map(|t| t.timestamp).unwrap_or(0) as u64;
+    let alt_cache = build_alt_cache(&block);
+
+    let mut transactions = Vec::new();
+    let mut block_fees = Vec::new();
+    let mut usage_by_account: std::collections::HashMap<String, AccountUsageAccumulator> =
+        std::collections::HashMap::new();
+
+    for transaction in &block.transactions {
+        if !transaction
+            .meta
+            .as_ref()
+            .map(|m| m.status.clone().unwrap_or_default().err.is_none())
+            .unwrap_or(false)
+        {
+            continue;
+        }
+        let Some(tx) = transaction.transaction.as_ref() else {
+            continue;
+        };
+
+        let transaction_hash = bs58::encode(&tx.signatures[0]).into_string();
+        let resolved_accounts = resolve_account_keys(transaction, &alt_cache, &alt_store);
+        let writable = writable_account_mask(transaction, resolved_accounts.len(), &alt_cache, &alt_store);
+        let fee_info = extract_compute_fee_info(transaction, &resolved_accounts);
+
+        if let Some(priority_fee) = fee_info.priority_fee_micro_lamports {
+            block_fees.push(priority_fee);
+        }
+
+        for (index, account) in resolved_accounts.iter().enumerate() {
+            let is_write_locked = writable.get(index).copied().unwrap_or(false);
+            let usage = usage_by_account.entry(account.clone()).or_insert_with(|| AccountUsageAccumulator {
+                is_write_locked,
+                ..Default::default()
+            });
+            usage.is_write_locked |= is_write_locked;
+            usage.cu_requested_total += fee_info.cu_requested.unwrap_or(0);
+            usage.cu_consumed_total += fee_info.cu_consumed.unwrap_or(0);
+            if let Some(priority_fee) = fee_info.priority_fee_micro_lamports {
+                usage.fees.push(priority_fee);
+            }
+        }
+
+        transactions.push(TransactionPrioFee {
+            transaction_hash,
+            cu_requested: fee_info.cu_requested,
+            cu_consumed: fee_info.cu_consumed,
+            priority_fee_micro_lamports: fee_info.priority_fee_micro_lamports,
+            total_fee: fee_info.total_fee,
+        });
+    }
+
+    let block_prio_fee = compute_fee_percentiles(&block_fees).map(to_prio_fee_data);
+
+    let account_usage = usage_by_account
+        .into_iter()
+        .map(|(key, usage)| AccountPriorityUsage {
+            key,
+            is_write_locked: usage.is_write_locked,
+            cu_requested_total: usage.cu_requested_total,
+            cu_consumed_total: usage.cu_consumed_total,
+            prio_fee_data: compute_fee_percentiles(&usage.fees).map(to_prio_fee_data),
+        })
+        .collect();
+
+    Ok(PriorityFeeStats {
+        block_number,
+        block_hash,
+        timestamp,
+        transactions,
+        block_prio_fee,
+        account_usage,
+    })
+}
+
+fn to_prio_fee_data(percentiles: FeePercentiles) -> PrioFeeData {
+    PrioFeeData {
+        min: percentiles.min,
+        median: percentiles.median,
+        p75: percentiles.p75,
+        p90: percentiles.p90,
+        p95: percentiles.p95,
+        max: percentiles.max,
+    }
+}
+
+/// Running per-account totals accumulated while walking a block's transactions.
+#[derive(Default)]
+struct AccountUsageAccumulator {
+    is_write_locked: bool,
+    cu_requested_total: u64,
+    cu_consumed_total: u64,
+    fees: Vec<u64>,
+}
+
+/// Output of the priority-fee analytics map module.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PriorityFeeStats {
+    #[prost(uint64, tag = "1")]
+    pub block_number: u64,
+    #[prost(string, tag = "2")]
+    pub block_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "3")]
+    pub timestamp: u64,
+    #[prost(message, repeated, tag = "4")]
+    pub transactions: ::prost::alloc::vec::Vec<TransactionPrioFee>,
+    #[prost(message, optional, tag = "5")]
+    pub block_prio_fee: ::core::option::Option<PrioFeeData>,
+    #[prost(message, repeated, tag = "6")]
+    pub account_usage: ::prost::alloc::vec::Vec<AccountPriorityUsage>,
+}
+
+/// Compute-budget and fee telemetry for a single transaction.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TransactionPrioFee {
+    #[prost(string, tag = "1")]
+    pub transaction_hash: ::prost::alloc::string::String,
+    #[prost(uint64, optional, tag = "2")]
+    pub cu_requested: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "3")]
+    pub cu_consumed: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "4")]
+    pub priority_fee_micro_lamports: ::core::option::Option<u64>,
+    #[prost(uint64, tag = "5")]
+    pub total_fee: u64,
+}
+
+/// Percentile summary of a set of per-transaction priority fees: `min`, `max`,
+/// `median`, `p75`, `p90`, `p95`, computed by sorting the fee vector and
+/// indexing at `len/2`, `len*75/100`, `len*90/100`, `len*95/100` respectively.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PrioFeeData {
+    #[prost(uint64, tag = "1")]
+    pub min: u64,
+    #[prost(uint64, tag = "2")]
+    pub max: u64,
+    #[prost(uint64, tag = "3")]
+    pub median: u64,
+    #[prost(uint64, tag = "4")]
+    pub p75: u64,
+    #[prost(uint64, tag = "5")]
+    pub p90: u64,
+    #[prost(uint64, tag = "6")]
+    pub p95: u64,
+}
+
+/// Aggregated compute-unit and priority-fee usage for a single account across
+/// every transaction in the block that touches it.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AccountPriorityUsage {
+    #[prost(string, tag = "1")]
+    pub key: ::prost::alloc::string::String,
+    #[prost(bool, tag = "2")]
+    pub is_write_locked: bool,
+    #[prost(uint64, tag = "3")]
+    pub cu_requested_total: u64,
+    #[prost(uint64, tag = "4")]
+    pub cu_consumed_total: u64,
+    #[prost(message, optional, tag = "5")]
+    pub prio_fee_data: ::core::option::Option<PrioFeeData>,
+}
@@ -1,15 +1,356 @@
 use substreams::log;
-use substreams_solana::pb::sf::solana::r#type::v1::Block;
+use substreams::store::{StoreGetProto, StoreGet};
+use substreams_solana::pb::sf::solana::r#type::v1::{Block, ConfirmedTransaction};
 use serde_json::Value;
 use std::collections::HashMap;
 use chrono::NaiveDateTime;
 
-/// Extract timestamp from a Solana block
+/// Extract the block timestamp, in milliseconds since the Unix epoch.
 pub fn extract_timestamp(block: &Block) -> u64 {
-    // Get timestamp in milliseconds
     block.block_time.as_ref().map(|t| t.timestamp_ms).unwrap_or(0) as u64
 }
 
+/// Program that owns Solana's address lookup table accounts.
+const ADDRESS_LOOKUP_TABLE_PROGRAM_ID: &str = "AddressLookupTab1e1111111111111111111111111";
+
+/// `ExtendLookupTable` instruction discriminator (first 4 bytes, little-endian u32).
+const EXTEND_LOOKUP_TABLE_IX: u32 = 2;
+
+/// Resolved contents of address lookup tables referenced by a block, keyed by the
+/// lookup table's own base58 pubkey. Built once per block and re-used across every
+/// transaction in that block so repeated lookups are O(1).
+pub type AltCache = HashMap<String, Vec<String>>;
+
+/// Scan a block's own `ExtendLookupTable` invocations to build a best-effort cache
+/// of lookup-table pubkey -> ordered list of addresses stored in that table.
+///
+/// This only sees tables that were created/extended within the block itself; tables
+/// that existed before the block (the common case) are not observable from block
+/// data alone, so callers should treat `resolve_account_keys` as best-effort and
+/// fall back to the legacy `account_keys` list when a referenced table isn't cached.
+pub fn build_alt_cache(block: &Block) -> AltCache {
+    let mut cache = AltCache::new();
+
+    for transaction in &block.transactions {
+        let Some(message) = transaction
+            .transaction
+            .as_ref()
+            .and_then(|t| t.message.as_ref())
+        else {
+            continue;
+        };
+
+        for instruction in &message.instructions {
+            let Some(program_key) = message
+                .account_keys
+                .get(instruction.program_id_index as usize)
+            else {
+                continue;
+            };
+            if program_key != ADDRESS_LOOKUP_TABLE_PROGRAM_ID {
+                continue;
+            }
+            if instruction.data.len() < 4 {
+                continue;
+            }
+            let discriminator = u32::from_le_bytes(instruction.data[0..4].try_into().unwrap());
+            if discriminator != EXTEND_LOOKUP_TABLE_IX {
+                continue;
+            }
+            let Some(&table_account_index) = instruction.accounts.first() else {
+                continue;
+            };
+            let Some(table_key) = message.account_keys.get(table_account_index as usize) else {
+                continue;
+            };
+
+            // Payload after the discriminator is a borsh-encoded Vec<Pubkey>: a
+            // 4-byte little-endian length prefix followed by that many 32-byte keys.
+            let payload = &instruction.data[4..];
+            if payload.len() < 4 {
+                continue;
+            }
+            let count = u32::from_le_bytes(payload[0..4].try_into().unwrap()) as usize;
+            let mut addresses = Vec::with_capacity(count);
+            let mut offset = 4;
+            for _ in 0..count {
+                if payload.len() < offset + 32 {
+                    break;
+                }
+                addresses.push(bs58::encode(&payload[offset..offset + 32]).into_string());
+                offset += 32;
+            }
+
+            cache.entry(table_key.clone()).or_insert_with(Vec::new).extend(addresses);
+        }
+    }
+
+    cache
+}
+
+/// A single address lookup table's contents, as persisted cross-block by
+/// `store_alt_tables` so that later blocks can resolve tables that were
+/// created/extended before them.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AltTable {
+    #[prost(string, tag = "1")]
+    pub table_key: ::prost::alloc::string::String,
+    #[prost(string, repeated, tag = "2")]
+    pub addresses: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+
+/// Output of the ALT-table-update extraction map module: one entry per lookup
+/// table that was created or extended in the block.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AltTableUpdates {
+    #[prost(message, repeated, tag = "1")]
+    pub tables: ::prost::alloc::vec::Vec<AltTable>,
+}
+
+/// Storage key prefix used for persisted address lookup tables in `store_alt_tables`.
+const ALT_TABLE_STORE_KEY_PREFIX: &str = "alt_table:";
+
+/// Extract the lookup tables created/extended in this block, for persisting
+/// into `store_alt_tables`. This is the same scan `build_alt_cache` performs,
+/// re-shaped into the map module's protobuf output.
+pub fn extract_alt_table_updates(block: &Block) -> AltTableUpdates {
+    let tables = build_alt_cache(block)
+        .into_iter()
+        .map(|(table_key, addresses)| AltTable { table_key, addresses })
+        .collect();
+    AltTableUpdates { tables }
+}
+
+/// Resolve the full, ordered account-key list for a transaction, handling both
+/// legacy and v0 (versioned) messages. For a v0 message, the validator itself
+/// resolves every referenced lookup table and records the result on
+/// `transaction.meta` as `loaded_writable_addresses`/`loaded_readonly_addresses`;
+/// that's authoritative and cheaper than reconstructing table contents
+/// ourselves, so it's used whenever present. Only when meta carries no loaded
+/// addresses (e.g. backfilled data with a stripped meta) do we fall back to
+/// resolving each `address_table_lookups` entry by index against a table found
+/// first in the current block's own `alt_cache` and, failing that, in
+/// `alt_store` -- the cross-block snapshot of every table this pipeline has
+/// observed being created/extended -- so tables created in earlier blocks
+/// still resolve correctly. Unversioned messages resolve to the legacy
+/// `account_keys` list alone; a referenced lookup table that isn't present in
+/// either source is skipped, so its addresses are missing from the result.
+pub fn resolve_account_keys(
+    transaction: &ConfirmedTransaction,
+    alt_cache: &AltCache,
+    alt_store: &StoreGetProto<AltTable>,
+) -> Vec<String> {
+    let Some(message) = transaction
+        .transaction
+        .as_ref()
+        .and_then(|t| t.message.as_ref())
+    else {
+        return Vec::new();
+    };
+
+    let mut resolved = message.account_keys.clone();
+
+    if message.address_table_lookups.is_empty() {
+        return resolved;
+    }
+
+    if let Some(meta) = transaction.meta.as_ref() {
+        if !meta.loaded_writable_addresses.is_empty() || !meta.loaded_readonly_addresses.is_empty() {
+            resolved.extend(meta.loaded_writable_addresses.iter().map(|a| bs58::encode(a).into_string()));
+            resolved.extend(meta.loaded_readonly_addresses.iter().map(|a| bs58::encode(a).into_string()));
+            return resolved;
+        }
+    }
+
+    let mut writable = Vec::new();
+    let mut readonly = Vec::new();
+
+    for lookup in &message.address_table_lookups {
+        let table_key = bs58::encode(&lookup.account_key).into_string();
+        let Some(table_addresses) = lookup_alt_table(&table_key, alt_cache, alt_store) else {
+            log::debug!("Address lookup table {} not found in ALT cache or store", table_key);
+            continue;
+        };
+
+        for &index in &lookup.writable_indexes {
+            if let Some(address) = table_addresses.get(index as usize) {
+                writable.push(address.clone());
+            }
+        }
+        for &index in &lookup.readonly_indexes {
+            if let Some(address) = table_addresses.get(index as usize) {
+                readonly.push(address.clone());
+            }
+        }
+    }
+
+    resolved.extend(writable);
+    resolved.extend(readonly);
+    resolved
+}
+
+/// Look up a lookup table's addresses, preferring the current block's own
+/// `alt_cache` and falling back to the cross-block `alt_store` snapshot.
+fn lookup_alt_table(
+    table_key: &str,
+    alt_cache: &AltCache,
+    alt_store: &StoreGetProto<AltTable>,
+) -> Option<Vec<String>> {
+    if let Some(addresses) = alt_cache.get(table_key) {
+        return Some(addresses.clone());
+    }
+    alt_store
+        .get_last(format!("{}{}", ALT_TABLE_STORE_KEY_PREFIX, table_key))
+        .map(|table| table.addresses)
+}
+
+/// Compute Budget program, used to request a compute-unit limit and to set a
+/// prioritization fee via a micro-lamports-per-CU price.
+const COMPUTE_BUDGET_PROGRAM_ID: &str = "ComputeBudget111111111111111111111111111111";
+const SET_COMPUTE_UNIT_LIMIT_IX: u8 = 2;
+const SET_COMPUTE_UNIT_PRICE_IX: u8 = 3;
+
+/// Compute-unit and fee telemetry recovered from a single transaction.
+#[derive(Debug, Clone, Default)]
+pub struct ComputeFeeInfo {
+    pub cu_requested: Option<u64>,
+    pub cu_consumed: Option<u64>,
+    pub priority_fee_micro_lamports: Option<u64>,
+    pub total_fee: u64,
+}
+
+/// Recover compute-budget and fee metrics for a transaction: the requested CU
+/// limit and price from any `ComputeBudgetInstruction` present, plus the actual
+/// CU consumption and total fee paid from the transaction meta.
+pub fn extract_compute_fee_info(
+    transaction: &ConfirmedTransaction,
+    resolved_accounts: &[String],
+) -> ComputeFeeInfo {
+    let mut info = ComputeFeeInfo {
+        total_fee: transaction.meta.as_ref().map(|m| m.fee).unwrap_or(0),
+        cu_consumed: transaction.meta.as_ref().and_then(|m| m.compute_units_consumed),
+        ..Default::default()
+    };
+
+    let Some(message) = transaction
+        .transaction
+        .as_ref()
+        .and_then(|t| t.message.as_ref())
+    else {
+        return info;
+    };
+
+    for instruction in &message.instructions {
+        let Some(program_key) = resolved_accounts.get(instruction.program_id_index as usize) else {
+            continue;
+        };
+        if program_key != COMPUTE_BUDGET_PROGRAM_ID {
+            continue;
+        }
+        let Some(&tag) = instruction.data.first() else {
+            continue;
+        };
+        match tag {
+            SET_COMPUTE_UNIT_LIMIT_IX if instruction.data.len() >= 5 => {
+                info.cu_requested = Some(u32::from_le_bytes(instruction.data[1..5].try_into().unwrap()) as u64);
+            }
+            SET_COMPUTE_UNIT_PRICE_IX if instruction.data.len() >= 9 => {
+                info.priority_fee_micro_lamports =
+                    Some(u64::from_le_bytes(instruction.data[1..9].try_into().unwrap()));
+            }
+            _ => {}
+        }
+    }
+
+    info
+}
+
+/// Percentile summary of a set of per-transaction priority fees.
+#[derive(Debug, Clone, Default)]
+pub struct FeePercentiles {
+    pub min: u64,
+    pub median: u64,
+    pub p75: u64,
+    pub p90: u64,
+    pub p95: u64,
+    pub max: u64,
+}
+
+/// Compute min/median/p75/p90/p95/max over a set of fees. Returns `None` when
+/// fewer than two samples are present.
+pub fn compute_fee_percentiles(fees: &[u64]) -> Option<FeePercentiles> {
+    if fees.len() < 2 {
+        return None;
+    }
+    let mut sorted = fees.to_vec();
+    sorted.sort_unstable();
+    let len = sorted.len();
+    let at = |p: usize| sorted[(len * p / 100).min(len - 1)];
+
+    Some(FeePercentiles {
+        min: sorted[0],
+        median: at(50),
+        p75: at(75),
+        p90: at(90),
+        p95: at(95),
+        max: sorted[len - 1],
+    })
+}
+
+/// Build a writable/readonly mask aligned to the account list returned by
+/// `resolve_account_keys` for the same transaction: static accounts follow the
+/// legacy signer/writable header layout, and any ALT-resolved accounts are
+/// writable-then-readonly as `resolve_account_keys` appends them.
+pub fn writable_account_mask(
+    transaction: &ConfirmedTransaction,
+    resolved_len: usize,
+    alt_cache: &AltCache,
+    alt_store: &StoreGetProto<AltTable>,
+) -> Vec<bool> {
+    let Some(message) = transaction
+        .transaction
+        .as_ref()
+        .and_then(|t| t.message.as_ref())
+    else {
+        return vec![false; resolved_len];
+    };
+
+    let header = message.header.clone().unwrap_or_default();
+    let num_static = message.account_keys.len();
+    let num_signers = header.num_required_signatures as usize;
+    let readonly_signed = header.num_readonly_signed_accounts as usize;
+    let readonly_unsigned = header.num_readonly_unsigned_accounts as usize;
+
+    let meta_loaded_writable = transaction.meta.as_ref().map(|m| m.loaded_writable_addresses.len());
+    let meta_loaded_readonly = transaction.meta.as_ref().map(|m| m.loaded_readonly_addresses.len());
+
+    let alt_writable_count = match (meta_loaded_writable, meta_loaded_readonly) {
+        (Some(w), Some(r)) if w > 0 || r > 0 => w,
+        _ => {
+            let mut count = 0usize;
+            for lookup in &message.address_table_lookups {
+                let table_key = bs58::encode(&lookup.account_key).into_string();
+                if lookup_alt_table(&table_key, alt_cache, alt_store).is_some() {
+                    count += lookup.writable_indexes.len();
+                }
+            }
+            count
+        }
+    };
+
+    (0..resolved_len)
+        .map(|i| {
+            if i >= num_static {
+                i < num_static + alt_writable_count
+            } else if i < num_signers {
+                i < num_signers.saturating_sub(readonly_signed)
+            } else {
+                i < num_static.saturating_sub(readonly_unsigned)
+            }
+        })
+        .collect()
+}
+
 /// Log decoded instruction for debugging purposes
 pub fn log_decoded_instruction(program_id: &str, ix_type: u8, instruction_data: &[u8]) {
     log::debug!("Program: {}, Instruction type: {}, Data length: {}",